@@ -1,23 +1,34 @@
-use crate::hundred_days::{
-    action::{active::Active, Action, GameState},
-    item::ItemCategory,
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    hundred_days::{
+        action::{active::Active, Action, GameState},
+        engine::{Command, Engine, Event},
+        game::{DayEvent, StopOn},
+        high_scores::{HighScore, HighScoreTable, HighScores},
+        report::Report,
+        research::{self, Tech},
+    },
+    save::{self, ReportFormat, SaveData, SaveSlot, MANUAL_SLOT_COUNT},
+    scenarios::{self, ScenarioEntry},
 };
 
+// the items of one category
 #[derive(Clone)]
 pub struct List {
+    pub category: String,
+    pub title: String,
     pub items: Vec<String>,
 }
 
-impl Default for List {
-    fn default() -> Self {
-        return List { items: Vec::new() };
-    }
-}
-
-#[derive(PartialEq, Clone)]
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
 pub struct HistoryItem {
     pub description: String,
     pub amount: i32,
+    // failed actions are shown differently
+    // and never counted as activations
+    #[serde(default)]
+    pub failed: bool,
 }
 
 impl HistoryItem {
@@ -25,17 +36,71 @@ impl HistoryItem {
         Self {
             description,
             amount,
+            failed: false,
+        }
+    }
+
+    pub fn failure(description: String) -> Self {
+        Self {
+            description,
+            amount: 1,
+            failed: true,
         }
     }
 }
 
 #[derive(PartialEq, Copy, Clone)]
 pub enum Table {
-    Resources,
-    Buildings,
+    // index into `App::tables`
+    Category(usize),
     Actions,
 }
 
+// tabs along the top of the game screen
+#[derive(PartialEq, Copy, Clone)]
+pub enum Tab {
+    MainGame,
+    NightMarket,
+    Research,
+    Progress,
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum Screen {
+    // shown on startup unless a scenario
+    // or save was given on the command line
+    Menu,
+    ScenarioPicker,
+    HighScores,
+    Game,
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum MenuEntry {
+    NewGame,
+    Continue,
+    HighScores,
+    Quit,
+}
+
+pub const MENU_ENTRIES: [MenuEntry; 4] = [
+    MenuEntry::NewGame,
+    MenuEntry::Continue,
+    MenuEntry::HighScores,
+    MenuEntry::Quit,
+];
+
+impl MenuEntry {
+    pub fn label(&self) -> &'static str {
+        match self {
+            MenuEntry::NewGame => "New game",
+            MenuEntry::Continue => "Continue",
+            MenuEntry::HighScores => "High scores",
+            MenuEntry::Quit => "Quit",
+        }
+    }
+}
+
 #[derive(PartialEq, Copy, Clone)]
 pub enum Direction {
     Up,
@@ -45,8 +110,8 @@ pub enum Direction {
 }
 
 pub struct App {
-    pub resource_table: List,
-    pub building_table: List,
+    // one per item category, in the order of the scenario
+    pub tables: Vec<List>,
 
     pub selection_index: usize,
 
@@ -56,47 +121,260 @@ pub struct App {
     // number of times to call an action
     // when an action is activated
     pub activation_amount: i32,
+    // what ends passing several days early
+    pub stop_on: StopOn,
 
     // selected tab
     pub selected_table: Table,
     // last selected item
     pub selected_item: String,
 
-    pub game_state: GameState,
+    pub screen: Screen,
+    // selected entry of the start menu
+    pub menu_index: usize,
+    // continue is only offered when there is an autosave
+    pub autosave_exists: bool,
+    // shown on the start menu when an entry failed
+    pub menu_message: Option<String>,
+    // read again every time the picker is opened
+    pub scenarios: Vec<ScenarioEntry>,
+    pub scenario_index: usize,
+    pub high_scores: Result<HighScores, String>,
+    // selected table on the high scores screen
+    pub high_score_index: usize,
+    pub tab: Tab,
+    // selected offer on the night market tab
+    pub offer_index: usize,
+    // selected tech on the research tab
+    pub tech_index: usize,
+    // manual slot used by save and load
+    pub save_slot: u8,
+
+    pub engine: Engine,
+    // made when the game ends, compared to the
+    // personal best from before this game
+    pub report: Option<Report>,
+    // high scores of the scenario once the game ended,
+    // including `recorded_score` if it was good enough
+    pub high_score_table: Option<HighScoreTable>,
+    pub recorded_score: Option<HighScore>,
+    pub player_name: String,
 }
 
 impl App {
-    pub fn new() -> App {
-        let game = GameState::generate_from_json();
-        let first_item = game
-            .items
-            .iter()
-            .find(|(_, i)| i.category == ItemCategory::Resource)
-            .and_then(|(s, _)| Some(s.to_owned()));
-
+    pub fn new(game: GameState) -> App {
         let mut app = App {
-            resource_table: List::default(),
-            building_table: List::default(),
+            tables: Vec::new(),
             selection_index: 0,
             activation_amount: 1,
-            selected_table: Table::Resources,
-            selected_item: first_item.unwrap_or_default(),
-            game_state: game,
+            stop_on: StopOn::default(),
+            selected_table: Table::Category(0),
+            selected_item: String::new(),
+            screen: Screen::Game,
+            menu_index: 0,
+            autosave_exists: false,
+            menu_message: None,
+            scenarios: Vec::new(),
+            scenario_index: 0,
+            high_scores: Ok(HighScores::default()),
+            high_score_index: 0,
+            tab: Tab::MainGame,
+            offer_index: 0,
+            tech_index: 0,
+            save_slot: 1,
+            engine: Engine::new(game),
+            report: None,
+            high_score_table: None,
+            recorded_score: None,
+            player_name: String::new(),
             history_limit: 3,
             history: Vec::new(),
         };
 
-        app.update_building_list();
-        app.update_resources_list();
+        app.update_tables();
+        app.change_tab(Table::Category(0));
         return app;
     }
 
+    pub fn to_save(&self) -> SaveData {
+        SaveData {
+            game_state: self.engine.state.clone(),
+            history: self.history.clone(),
+            activation_amount: self.activation_amount,
+            stop_on: self.stop_on,
+        }
+    }
+
+    pub fn apply_save(&mut self, data: SaveData) {
+        self.engine = Engine::new(data.game_state);
+        self.report = None;
+        self.high_score_table = None;
+        self.recorded_score = None;
+        self.history = data.history;
+        self.activation_amount = data.activation_amount;
+        self.stop_on = data.stop_on;
+
+        self.update_tables();
+        self.change_tab(Table::Category(0));
+    }
+
+    pub fn open_menu(&mut self) {
+        self.screen = Screen::Menu;
+        self.autosave_exists = save::exists(SaveSlot::Auto);
+    }
+
+    // moves through the list of whichever menu screen is open
+    pub fn navigate_menu(&mut self, direction: Direction) {
+        let (index, count) = match self.screen {
+            Screen::Menu => (&mut self.menu_index, MENU_ENTRIES.len()),
+            Screen::ScenarioPicker => (&mut self.scenario_index, self.scenarios.len()),
+            Screen::HighScores => {
+                let count = self
+                    .high_scores
+                    .as_ref()
+                    .map_or(0, |high_scores| high_scores.tables.len());
+                (&mut self.high_score_index, count)
+            }
+            Screen::Game => return,
+        };
+        if count == 0 {
+            return;
+        }
+
+        *index = match direction {
+            Direction::Up => (*index + count - 1) % count,
+            Direction::Down => (*index + 1) % count,
+            _ => *index,
+        };
+    }
+
+    pub fn open_scenario_picker(&mut self) {
+        self.scenarios = scenarios::list();
+        self.scenario_index = 0;
+        self.screen = Screen::ScenarioPicker;
+    }
+
+    // none when the selected scenario is invalid
+    pub fn selected_scenario(&self) -> Option<GameState> {
+        let entry = self.scenarios.get(self.scenario_index)?;
+
+        return entry.game.as_ref().ok().cloned();
+    }
+
+    pub fn open_high_scores(&mut self) {
+        self.high_scores = save::read_high_scores().map_err(|err| err.to_string());
+        self.high_score_index = 0;
+        self.screen = Screen::HighScores;
+    }
+
+    pub fn start_game(&mut self, game: GameState) {
+        self.apply_save(SaveData {
+            game_state: game,
+            history: Vec::new(),
+            activation_amount: 1,
+            stop_on: self.stop_on,
+        });
+        self.open_tab(Tab::MainGame);
+        self.screen = Screen::Game;
+    }
+
+    // stays on the menu if the autosave cannot be read
+    pub fn continue_from_autosave(&mut self) {
+        match save::read(SaveSlot::Auto) {
+            Ok(data) => {
+                self.apply_save(data);
+                self.open_tab(Tab::MainGame);
+                self.screen = Screen::Game;
+            }
+            Err(err) => {
+                self.menu_message = Some(format!("Could not continue from autosave: {err}"))
+            }
+        }
+    }
+
+    // never, on events, when an item runs
+    // out, or on both, then never again
+    pub fn cycle_stop_on(&mut self) {
+        self.stop_on = match (self.stop_on.event, self.stop_on.depleted) {
+            (false, false) => StopOn {
+                event: true,
+                depleted: false,
+            },
+            (true, false) => StopOn {
+                event: false,
+                depleted: true,
+            },
+            (false, true) => StopOn {
+                event: true,
+                depleted: true,
+            },
+            (true, true) => StopOn::default(),
+        };
+    }
+
+    pub fn cycle_save_slot(&mut self) {
+        self.save_slot = self.save_slot % MANUAL_SLOT_COUNT + 1;
+    }
+
+    // hardcore games are only saved when quitting, a
+    // slot to go back to would work like an undo
+    fn slots_disabled(&mut self) -> bool {
+        if self.engine.state.hardcore {
+            self.add_history_item(HistoryItem::failure(
+                "Save slots are disabled in hardcore mode".to_string(),
+            ));
+        }
+
+        return self.engine.state.hardcore;
+    }
+
+    pub fn save_to_slot(&mut self) {
+        if self.slots_disabled() {
+            return;
+        }
+
+        let slot = self.save_slot;
+        let history_item = match save::write(SaveSlot::Manual(slot), &self.to_save()) {
+            Ok(()) => HistoryItem::new(format!("Saved game to slot {slot}"), 1),
+            Err(err) => HistoryItem::failure(format!("Could not save to slot {slot}: {err}")),
+        };
+
+        self.add_history_item(history_item);
+    }
+
+    pub fn load_from_slot(&mut self) {
+        if self.slots_disabled() {
+            return;
+        }
+
+        let slot = self.save_slot;
+        let history_item = match save::read(SaveSlot::Manual(slot)) {
+            Ok(data) => {
+                self.apply_save(data);
+                HistoryItem::new(format!("Loaded game from slot {slot}"), 1)
+            }
+            Err(err) => HistoryItem::failure(format!("Could not load slot {slot}: {err}")),
+        };
+
+        self.add_history_item(history_item);
+    }
+
+    // called when the player quits, a finished game
+    // is not worth continuing so its autosave is dropped
+    pub fn autosave(&self) -> std::io::Result<()> {
+        if self.engine.state.day < 0 {
+            return save::remove(SaveSlot::Auto);
+        }
+
+        return save::write(SaveSlot::Auto, &self.to_save());
+    }
+
     fn selected_action(&self) -> Option<Active> {
-        let Some(item) = self.game_state.items.get(&self.selected_item) else {
+        let Some(item) = self.engine.state.items.get(&self.selected_item) else {
             return None;
         };
 
-        return Some(item.actions_active[self.selection_index].clone());
+        return item.actions_active.get(self.selection_index).cloned();
     }
 
     fn add_history_item(&mut self, history_item: HistoryItem) {
@@ -106,7 +384,9 @@ impl App {
             return;
         };
 
-        if first_item.description == history_item.description {
+        if first_item.description == history_item.description
+            && first_item.failed == history_item.failed
+        {
             self.history[0].amount += history_item.amount;
         } else {
             self.history.insert(0, history_item);
@@ -117,46 +397,118 @@ impl App {
         }
     }
 
-    fn update_resources_list(&mut self) {
-        self.resource_table.items = self
-            .game_state
-            .items
-            .iter()
-            .filter(|(_item_name, item)| item.category == ItemCategory::Resource)
-            .map(|(item_name, _)| {
-                return item_name.to_string();
+    // items stay out of the tables until they are researched
+    fn update_tables(&mut self) {
+        let state = &self.engine.state;
+        self.tables = state
+            .item_categories()
+            .into_iter()
+            .map(|category| List {
+                items: state
+                    .items
+                    .iter()
+                    .filter(|(item_name, item)| {
+                        item.category == category.name && !research::is_locked(state, item_name)
+                    })
+                    .map(|(item_name, _)| {
+                        return item_name.to_string();
+                    })
+                    .collect(),
+                title: category.title().to_string(),
+                category: category.name,
             })
             .collect();
     }
 
-    fn update_building_list(&mut self) {
-        self.building_table.items = self
-            .game_state
-            .items
+    pub fn currently_selected_item_name(&self) -> Option<String> {
+        match self.selected_table {
+            Table::Category(index) => self
+                .tables
+                .get(index)?
+                .items
+                .get(self.selection_index)
+                .cloned(),
+            Table::Actions => None,
+        }
+    }
+
+    // the table the selected item is listed in
+    pub fn selected_item_table(&self) -> Option<usize> {
+        let item = self.engine.state.items.get(&self.selected_item)?;
+
+        return self
+            .tables
             .iter()
-            .filter(|(_item_name, item)| item.category == ItemCategory::Building)
-            .map(|(item_name, _)| {
-                return item_name.to_string();
-            })
-            .collect();
+            .position(|table| table.category == item.category);
     }
 
-    pub fn currently_selected_item_name(&self) -> Option<String> {
-        match self.selected_table {
-            Table::Resources => self.resource_table.items.get(self.selection_index).cloned(),
-            Table::Buildings => self.building_table.items.get(self.selection_index).cloned(),
-            _ => None,
+    pub fn switch_tab(&mut self) {
+        self.open_tab(match self.tab {
+            Tab::MainGame => Tab::NightMarket,
+            Tab::NightMarket => Tab::Research,
+            Tab::Research => Tab::Progress,
+            Tab::Progress => Tab::MainGame,
+        });
+    }
+
+    pub fn open_tab(&mut self, tab: Tab) {
+        self.tab = tab;
+        self.offer_index = 0;
+        self.tech_index = 0;
+    }
+
+    // techs are listed in the order of the scenario
+    pub fn research_selected_tech(&self) -> Option<&Tech> {
+        return self.engine.state.techs.get(self.tech_index);
+    }
+
+    fn navigate_techs(&mut self, direction: Direction) {
+        let tech_count = self.engine.state.techs.len();
+        if tech_count == 0 {
+            return;
         }
+
+        self.tech_index = match direction {
+            Direction::Up => (self.tech_index + tech_count - 1) % tech_count,
+            Direction::Down => (self.tech_index + 1) % tech_count,
+            _ => self.tech_index,
+        };
+    }
+
+    fn navigate_offers(&mut self, direction: Direction) {
+        let offer_count = self
+            .engine
+            .state
+            .night_market
+            .as_ref()
+            .map_or(0, |night_market| night_market.stocked.len());
+        if offer_count == 0 {
+            return;
+        }
+
+        self.offer_index = match direction {
+            Direction::Up => (self.offer_index + offer_count - 1) % offer_count,
+            Direction::Down => (self.offer_index + 1) % offer_count,
+            _ => self.offer_index,
+        };
     }
 
     pub fn navigate(&mut self, direction: Direction) {
+        match self.tab {
+            Tab::MainGame => {}
+            Tab::NightMarket => return self.navigate_offers(direction),
+            Tab::Research => return self.navigate_techs(direction),
+            Tab::Progress => return,
+        }
+
         let mut selection_index_wrapping_add = |amount: i32| {
             let max = match self.selected_table {
-                Table::Resources => self.resource_table.items.len(),
-                Table::Buildings => self.building_table.items.len(),
+                Table::Category(index) => {
+                    self.tables.get(index).map_or(0, |table| table.items.len())
+                }
                 Table::Actions => {
                     let selected_item = self.selected_item.clone();
-                    let Some(item) = self.game_state.items.get(&selected_item) else {
+                    let Some(item) = self.engine.state.items.get(&selected_item) else {
                         return;
                     };
 
@@ -164,6 +516,10 @@ impl App {
                 }
             };
 
+            if max == 0 {
+                return;
+            }
+
             let added = self.selection_index as i32 + amount;
             self.selection_index = if (0..max).contains(&(added as usize)) {
                 added as usize
@@ -176,18 +532,25 @@ impl App {
             };
         };
 
+        // the category tables in order with the
+        // actions last, wrapping around at both ends
+        let table_count = self.tables.len();
         match direction {
             Direction::Up => selection_index_wrapping_add(-1),
             Direction::Down => selection_index_wrapping_add(1),
             Direction::Left => match self.selected_table {
-                Table::Actions => self.change_tab(Table::Buildings),
-                Table::Buildings => self.change_tab(Table::Resources),
-                Table::Resources => self.change_tab(Table::Actions),
+                Table::Actions if table_count > 0 => {
+                    self.change_tab(Table::Category(table_count - 1))
+                }
+                Table::Category(0) | Table::Actions => self.change_tab(Table::Actions),
+                Table::Category(index) => self.change_tab(Table::Category(index - 1)),
             },
             Direction::Right => match self.selected_table {
-                Table::Buildings => self.change_tab(Table::Actions),
-                Table::Resources => self.change_tab(Table::Buildings),
-                Table::Actions => self.change_tab(Table::Resources),
+                Table::Category(index) if index + 1 < table_count => {
+                    self.change_tab(Table::Category(index + 1))
+                }
+                Table::Category(_) => self.change_tab(Table::Actions),
+                Table::Actions => self.change_tab(Table::Category(0)),
             },
         }
 
@@ -202,7 +565,7 @@ impl App {
         self.selected_table = new_table;
         self.selection_index = 0;
 
-        if self.selected_table == Table::Resources || self.selected_table == Table::Buildings {
+        if let Table::Category(_) = self.selected_table {
             if let Some(selected_item_name) = self.currently_selected_item_name() {
                 self.selected_item = selected_item_name;
             }
@@ -211,16 +574,343 @@ impl App {
 
     pub fn call_selected_action(&mut self) {
         let Some(action) = self.selected_action() else {
-            self.add_history_item(HistoryItem::new("Could not find action".to_string(), 1));
+            self.add_history_item(HistoryItem::failure("Could not find action".to_string()));
+            return;
+        };
+
+        let events = self.engine.execute(Command::Activate {
+            item: self.selected_item.clone(),
+            action: action.name().to_string(),
+            amount: self.activation_amount,
+        });
+
+        self.add_history_events(events);
+    }
+
+    pub fn trade_selected_offer(&mut self) {
+        let events = self.engine.execute(Command::Trade {
+            slot: self.offer_index,
+            amount: self.activation_amount,
+        });
+
+        self.add_history_events(events);
+    }
+
+    // cancels the build of the selected item that was queued last
+    pub fn cancel_selected_build(&mut self) {
+        let slot = self
+            .engine
+            .state
+            .build_queue
+            .iter()
+            .rposition(|build| build.item == self.selected_item);
+        let Some(slot) = slot else {
+            self.add_history_item(HistoryItem::failure(format!(
+                "No {} is being built",
+                self.selected_item
+            )));
+            return;
+        };
+
+        let events = self.engine.execute(Command::Cancel { slot });
+
+        self.add_history_events(events);
+    }
+
+    pub fn research_selected(&mut self) {
+        let Some(tech) = self.research_selected_tech() else {
+            return;
+        };
+
+        let events = self.engine.execute(Command::Research {
+            tech: tech.name.clone(),
+        });
+
+        self.add_history_events(events);
+    }
+
+    pub fn pass_days(&mut self) {
+        let events = self.engine.execute(Command::PassDays {
+            days: self.activation_amount,
+            stop_on: self.stop_on,
+        });
+
+        self.add_history_events(events);
+    }
+
+    pub fn undo(&mut self) {
+        let events = self.engine.execute(Command::Undo);
+
+        self.add_history_events(events);
+        if !self.engine.is_over() && self.report.is_some() {
+            self.unfinish_game();
+        }
+    }
+
+    // adds the result to the high scores and compares
+    // it to the best earlier result of the scenario
+    fn finish_game(&mut self) {
+        let state = &self.engine.state;
+        let mut high_scores = match save::read_high_scores() {
+            Ok(high_scores) => high_scores,
+            Err(err) => {
+                // never overwrite scores that could not be read
+                self.report = Some(Report::new(state, None));
+                self.add_history_item(HistoryItem::failure(format!(
+                    "Could not read high scores: {err}"
+                )));
+                return;
+            }
+        };
+
+        let table = high_scores
+            .tables
+            .entry(state.scenario.key(state.seed))
+            .or_insert_with(|| HighScoreTable {
+                scenario: state.scenario.name.clone(),
+                seed: state.seed,
+                scores: Vec::new(),
+            });
+        let report = Report::new(state, table.best());
+        let score = HighScore {
+            name: self.player_name.clone(),
+            net_worth: report.net_worth,
+            seed: state.seed,
+            date: save::today(),
+        };
+
+        self.report = Some(report);
+        if table.insert(score.clone()).is_some() {
+            self.recorded_score = Some(score);
+        }
+        self.high_score_table = Some(table.clone());
+
+        if let Err(err) = save::write_high_scores(&high_scores) {
+            self.recorded_score = None;
+            self.add_history_item(HistoryItem::failure(format!(
+                "Could not save high scores: {err}"
+            )));
+        }
+    }
+
+    // takes back the result of a game
+    // that is played on after an undo
+    fn unfinish_game(&mut self) {
+        self.report = None;
+        self.high_score_table = None;
+
+        let Some(score) = self.recorded_score.take() else {
             return;
         };
+        let state = &self.engine.state;
+        let result = save::read_high_scores().and_then(|mut high_scores| {
+            if let Some(table) = high_scores.tables.get_mut(&state.scenario.key(state.seed)) {
+                table.remove(&score);
+            }
+            save::write_high_scores(&high_scores)
+        });
+
+        if let Err(err) = result {
+            self.add_history_item(HistoryItem::failure(format!(
+                "Could not remove the high score: {err}"
+            )));
+        }
+    }
+
+    pub fn export_report(&mut self, format: ReportFormat) {
+        let report = self
+            .report
+            .clone()
+            .unwrap_or_else(|| Report::new(&self.engine.state, None));
+
+        let history_item = match save::export_report(&report, format) {
+            Ok(path) => HistoryItem::new(format!("Exported report to {}", path.display()), 1),
+            Err(err) => HistoryItem::failure(format!("Could not export report: {err}")),
+        };
+        self.add_history_item(history_item);
+    }
+
+    pub fn redo(&mut self) {
+        let events = self.engine.execute(Command::Redo);
 
-        let action_desc = action.activate(
-            self.selected_item.clone(),
-            &mut self.game_state,
-            self.activation_amount,
+        self.add_history_events(events);
+    }
+
+    fn add_history_events(&mut self, events: Vec<Event>) {
+        for event in events {
+            let history_item = match event {
+                Event::ActionApplied {
+                    item,
+                    action,
+                    amount,
+                    changes,
+                } => HistoryItem::new(format!("{action} {item}: {changes}"), amount),
+                Event::CommandRejected { reason, .. } => HistoryItem::failure(reason.to_string()),
+                Event::Traded {
+                    offer,
+                    amount,
+                    changes,
+                } => HistoryItem::new(format!("{offer}: {changes}"), amount),
+                Event::Undone { changes } => HistoryItem::new(format!("Undo: {changes}"), 1),
+                Event::BuildCancelled {
+                    item,
+                    amount,
+                    changes,
+                } => HistoryItem::new(format!("Cancelled {item} x{amount}: {changes}"), 1),
+                Event::ResearchStarted {
+                    tech,
+                    days: 0,
+                    changes,
+                } => HistoryItem::new(format!("Researched {tech}: {changes}"), 1),
+                Event::ResearchStarted {
+                    tech,
+                    days,
+                    changes,
+                } => HistoryItem::new(
+                    format!("Researching {tech} for {days} day(s): {changes}"),
+                    1,
+                ),
+                Event::Redone { changes } => HistoryItem::new(format!("Redo: {changes}"), 1),
+                // passing days is visible in the day counter, only
+                // what happened on them is added, once per kind
+                Event::DaysPassed {
+                    requested, passed, ..
+                } => {
+                    let mut repeated: Vec<(&DayEvent, i32)> = Vec::new();
+                    for day_event in passed.events() {
+                        match repeated
+                            .iter_mut()
+                            .find(|(seen, _)| seen.summary() == day_event.summary())
+                        {
+                            Some((_, count)) => *count += 1,
+                            None => repeated.push((day_event, 1)),
+                        }
+                    }
+
+                    for (day_event, count) in repeated {
+                        let mut history_item = match day_event {
+                            DayEvent::UpkeepUnpaid { .. }
+                            | DayEvent::UpkeepDebt { .. }
+                            | DayEvent::ConversionShort { .. }
+                            | DayEvent::Objective { reached: false, .. } => {
+                                HistoryItem::failure(day_event.summary())
+                            }
+                            DayEvent::RandomEvent { .. }
+                            | DayEvent::Constructed { .. }
+                            | DayEvent::ResearchFinished { .. }
+                            | DayEvent::Objective { .. } => {
+                                HistoryItem::new(day_event.summary(), 1)
+                            }
+                        };
+                        history_item.amount = count;
+                        self.add_history_item(history_item);
+                    }
+
+                    let passed_days = passed.days.len();
+                    match &passed.stopped {
+                        Some(reason) if passed_days < requested as usize => {
+                            self.add_history_item(HistoryItem::new(
+                                format!("Stopped after {passed_days} day(s): {reason}"),
+                                1,
+                            ))
+                        }
+                        _ => {}
+                    }
+                    continue;
+                }
+                Event::GameEnded { .. } => {
+                    self.finish_game();
+                    continue;
+                }
+            };
+
+            self.add_history_item(history_item);
+        }
+
+        self.update_selection();
+    }
+
+    // research and undoing it can show or hide items and
+    // actions, so the selection may point past the end
+    fn update_selection(&mut self) {
+        self.update_tables();
+        match self.selected_table {
+            Table::Category(_) => {
+                if self.currently_selected_item_name().is_none() {
+                    self.change_tab(self.selected_table);
+                }
+            }
+            Table::Actions => {
+                let action_count = self
+                    .engine
+                    .state
+                    .items
+                    .get(&self.selected_item)
+                    .map_or(0, |item| item.actions_active.len());
+                self.selection_index = self.selection_index.min(action_count.saturating_sub(1));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Amber can only be sold until Trade adds a Buy action
+    fn app() -> App {
+        let scenario = r#"{
+            "day": 10,
+            "currency": 100,
+            "items": {
+                "Amber": { "name": "Amber", "amount": 0, "category": "Resource",
+                    "actions_active": [{ "Sell": { "sell_price": 5 } }],
+                    "actions_passive": [] }
+            },
+            "techs": [
+                { "name": "Trade", "unlocks": [
+                    { "Action": { "item": "Amber", "action": { "Buy": { "buy_price": 5 } } } }
+                ] }
+            ]
+        }"#;
+
+        return App::new(GameState::generate_from_json("test.json", scenario).unwrap());
+    }
+
+    #[test]
+    fn undoing_an_unlocked_action_keeps_the_selection_on_an_action() {
+        let mut app = app();
+        app.research_selected();
+        app.change_tab(Table::Actions);
+        app.navigate(Direction::Down);
+        assert_eq!(
+            app.selected_action(),
+            Some(Active::Buy {
+                buy_price: 5.into()
+            })
         );
 
-        self.add_history_item(HistoryItem::new(action_desc, self.activation_amount));
+        app.undo();
+        assert_eq!(app.selection_index, 0);
+        assert!(matches!(app.selected_action(), Some(Active::Sell { .. })));
+
+        app.redo();
+        app.navigate(Direction::Down);
+        app.undo();
+        app.call_selected_action();
+        assert_eq!(app.engine.state.items["Amber"].amount, 0.into());
+    }
+
+    #[test]
+    fn hardcore_games_cannot_use_save_slots() {
+        let mut app = app();
+        app.engine.state.hardcore = true;
+
+        app.save_to_slot();
+        app.load_from_slot();
+
+        assert_eq!(app.history.len(), 1);
+        assert!(app.history[0].failed);
+        assert_eq!(app.history[0].amount, 2);
     }
 }
@@ -0,0 +1,253 @@
+use std::{env, error::Error, fmt, path::PathBuf};
+
+use crate::hundred_days::{game::GameState, validation::ScenarioError};
+
+pub const USAGE: &str = "\
+Usage: terminal_hundred_days [OPTIONS]
+
+Options:
+  -s, --scenario <PATH>  Scenario file to start a new game from
+  -l, --save <PATH>      Save file to continue a game from
+  -d, --day <DAYS>       Override the number of days the game starts with
+      --seed <SEED>      Seed for everything random, the same seed
+                         and scenario always play out the same way
+      --script <PATH>    Play the scenario or saved game without a
+                         terminal by running the commands in PATH,
+                         one per line:
+                         `pass <days> [until event|depleted]`,
+                         `trade <offer> [amount]`, `cancel <build>`,
+                         `research <tech>`, `undo`, `redo`
+                         or `<action> <item> [amount]`
+      --report <PATH>    After a script, write the game report to PATH,
+                         as Markdown for `.md` files and JSON otherwise
+      --name <NAME>      Name to put on high scores, defaults to
+                         the name of the logged in user
+      --high-scores      Print the high scores of every scenario
+  -h, --help             Print this message";
+
+#[derive(Default)]
+pub struct Args {
+    pub scenario: Option<PathBuf>,
+    pub save: Option<PathBuf>,
+    pub day: Option<i32>,
+    pub seed: Option<u64>,
+    pub script: Option<PathBuf>,
+    pub report: Option<PathBuf>,
+    pub name: Option<String>,
+    pub high_scores: bool,
+    pub help: bool,
+}
+
+#[derive(Debug)]
+pub enum ArgsError {
+    MissingValue(String),
+    InvalidValue { flag: String, value: String },
+    UnknownArgument(String),
+    Conflicting(String, String),
+    Requires(String, String),
+}
+
+impl fmt::Display for ArgsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArgsError::MissingValue(flag) => write!(f, "{flag} expects a value"),
+            ArgsError::InvalidValue { flag, value } => {
+                write!(f, "`{value}` is not a valid value for {flag}")
+            }
+            ArgsError::UnknownArgument(arg) => write!(f, "unknown argument `{arg}`"),
+            ArgsError::Conflicting(a, b) => write!(f, "{a} cannot be used together with {b}"),
+            ArgsError::Requires(a, b) => write!(f, "{a} can only be used with {b}"),
+        }
+    }
+}
+
+impl Error for ArgsError {}
+
+impl Args {
+    pub fn from_env() -> Result<Self, ArgsError> {
+        return Self::parse(env::args().skip(1));
+    }
+
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, ArgsError> {
+        let mut parsed = Args::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            // accept both `--flag value` and `--flag=value`
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => {
+                    (flag.to_string(), Some(value.to_string()))
+                }
+                _ => (arg.clone(), None),
+            };
+
+            let mut value = || {
+                inline_value
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| ArgsError::MissingValue(flag.clone()))
+            };
+
+            match flag.as_str() {
+                "-s" | "--scenario" => parsed.scenario = Some(PathBuf::from(value()?)),
+                "-l" | "--save" => parsed.save = Some(PathBuf::from(value()?)),
+                "-d" | "--day" => parsed.day = Some(parse_days(&flag, value()?)?),
+                "--seed" => parsed.seed = Some(parse_number(&flag, value()?)?),
+                "--script" => parsed.script = Some(PathBuf::from(value()?)),
+                "--report" => parsed.report = Some(PathBuf::from(value()?)),
+                "--name" => parsed.name = Some(value()?),
+                "--high-scores" => parsed.high_scores = true,
+                "-h" | "--help" => parsed.help = true,
+                _ => return Err(ArgsError::UnknownArgument(arg)),
+            }
+        }
+
+        if parsed.scenario.is_some() && parsed.save.is_some() {
+            return Err(ArgsError::Conflicting(
+                "--scenario".to_string(),
+                "--save".to_string(),
+            ));
+        }
+
+        // a saved game keeps the days and random
+        // sequence it was saved with
+        if parsed.save.is_some() {
+            let overrides = [
+                ("--day", parsed.day.is_some()),
+                ("--seed", parsed.seed.is_some()),
+            ];
+            if let Some((flag, _)) = overrides.into_iter().find(|(_, given)| *given) {
+                return Err(ArgsError::Conflicting(
+                    flag.to_string(),
+                    "--save".to_string(),
+                ));
+            }
+        }
+
+        if parsed.report.is_some() && parsed.script.is_none() {
+            return Err(ArgsError::Requires(
+                "--report".to_string(),
+                "--script".to_string(),
+            ));
+        }
+
+        return Ok(parsed);
+    }
+
+    pub fn load_scenario(&self) -> Result<GameState, ScenarioError> {
+        let mut game = match &self.scenario {
+            Some(path) => GameState::generate_from_file(path)?,
+            None => GameState::generate_default(),
+        };
+
+        self.apply_overrides(&mut game);
+        return Ok(game);
+    }
+
+    pub fn player_name(&self) -> String {
+        return self
+            .name
+            .clone()
+            .or_else(|| env::var("USER").ok())
+            .or_else(|| env::var("USERNAME").ok())
+            .unwrap_or_else(|| "Player".to_string());
+    }
+
+    pub fn apply_overrides(&self, game: &mut GameState) {
+        if let Some(day) = self.day {
+            game.override_day(day);
+        }
+        if let Some(seed) = self.seed {
+            game.reseed(seed);
+        }
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(flag: &str, value: String) -> Result<T, ArgsError> {
+    return value.parse().map_err(|_| ArgsError::InvalidValue {
+        flag: flag.to_string(),
+        value,
+    });
+}
+
+// a game starting without days left would already be over
+fn parse_days(flag: &str, value: String) -> Result<i32, ArgsError> {
+    let days = parse_number(flag, value.clone())?;
+    if days < 1 {
+        return Err(ArgsError::InvalidValue {
+            flag: flag.to_string(),
+            value,
+        });
+    }
+
+    return Ok(days);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, ArgsError> {
+        return Args::parse(args.iter().map(|arg| arg.to_string()));
+    }
+
+    fn conflict(args: &[&str]) -> Option<(String, String)> {
+        return match parse(args) {
+            Err(ArgsError::Conflicting(a, b)) => Some((a, b)),
+            _ => None,
+        };
+    }
+
+    #[test]
+    fn parses_flags_and_inline_values() {
+        let args = parse(&["-s", "game.json", "--seed=7", "--day", "20"]).unwrap();
+
+        assert_eq!(args.scenario, Some(PathBuf::from("game.json")));
+        assert_eq!(args.seed, Some(7));
+        assert_eq!(args.day, Some(20));
+    }
+
+    #[test]
+    fn saves_conflict_with_scenarios_and_overrides() {
+        let pair = |a: &str, b: &str| Some((a.to_string(), b.to_string()));
+
+        assert_eq!(
+            conflict(&["-s", "game.json", "-l", "save.json"]),
+            pair("--scenario", "--save")
+        );
+        assert_eq!(
+            conflict(&["-l", "save.json", "--seed", "3"]),
+            pair("--seed", "--save")
+        );
+        assert_eq!(
+            conflict(&["--day", "5", "-l", "save.json"]),
+            pair("--day", "--save")
+        );
+        assert_eq!(conflict(&["-l", "save.json"]), None);
+    }
+
+    #[test]
+    fn rejects_bad_arguments() {
+        assert!(matches!(
+            parse(&["--report", "report.md"]),
+            Err(ArgsError::Requires(..))
+        ));
+        assert!(matches!(
+            parse(&["--seed", "soon"]),
+            Err(ArgsError::InvalidValue { .. })
+        ));
+        assert!(matches!(parse(&["--day"]), Err(ArgsError::MissingValue(_))));
+        assert!(matches!(
+            parse(&["--day", "0"]),
+            Err(ArgsError::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            parse(&["-d", "-3"]),
+            Err(ArgsError::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            parse(&["--fast"]),
+            Err(ArgsError::UnknownArgument(_))
+        ));
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{
-    app::{App, Table},
-    hundred_days::item::ItemCategory,
+    app::{App, MenuEntry, Screen, Tab, Table, MENU_ENTRIES},
+    cli::Args,
+    save::{self, ReportFormat},
     ui::draw,
 };
 use crossterm::{
@@ -25,9 +26,23 @@ enum Inputs {
     DecreaseActionActivation,
     ActivateOrGoToActions,
     PassDay,
+    CancelBuild,
+    SaveGame,
+    LoadGame,
+    CycleSaveSlot,
+    Undo,
+    Redo,
+    SwitchTab,
+    CycleStopOn,
+    ExportJson,
+    ExportMarkdown,
 }
 
-pub fn run() -> Result<(), Box<dyn Error>> {
+pub fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    // load the game before touching the terminal
+    // so errors are printed normally
+    let app = create_app(&args)?;
+
     // setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -35,9 +50,8 @@ pub fn run() -> Result<(), Box<dyn Error>> {
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    // create app and run it
-    let app = App::new();
-    let res = run_app(&mut terminal, app);
+    // run app
+    let res = run_app(&mut terminal, app, &args);
 
     // restore terminal
     disable_raw_mode()?;
@@ -55,13 +69,72 @@ pub fn run() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
-fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<()> {
+fn create_app(args: &Args) -> Result<App, Box<dyn Error>> {
+    let mut app = if let Some(path) = &args.save {
+        let data = save::read_path(path)
+            .map_err(|err| format!("Could not load save {}: {err}", path.display()))?;
+
+        let mut app = App::new(data.game_state.clone());
+        app.apply_save(data);
+        app
+    } else {
+        App::new(args.load_scenario()?)
+    };
+    app.player_name = args.player_name();
+
+    // the menu is skipped when the player
+    // asked for a specific game
+    if args.save.is_none() && args.scenario.is_none() {
+        app.open_menu();
+    }
+
+    return Ok(app);
+}
+
+fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App, args: &Args) -> io::Result<()> {
     loop {
         terminal.draw(|f| draw(f, &mut app))?;
 
         let mut action = None;
 
         if let Event::Key(key) = event::read()? {
+            // nothing is autosaved when quitting from the
+            // menus, no game has been played yet
+            if app.screen != Screen::Game {
+                match key.code {
+                    KeyCode::Up | KeyCode::Char('w') => {
+                        app.navigate_menu(crate::app::Direction::Up)
+                    }
+                    KeyCode::Down | KeyCode::Char('s') => {
+                        app.navigate_menu(crate::app::Direction::Down)
+                    }
+                    KeyCode::Enter => match app.screen {
+                        Screen::Menu => match MENU_ENTRIES[app.menu_index] {
+                            MenuEntry::NewGame => app.open_scenario_picker(),
+                            MenuEntry::Continue if app.autosave_exists => {
+                                app.continue_from_autosave()
+                            }
+                            MenuEntry::Continue => {}
+                            MenuEntry::HighScores => app.open_high_scores(),
+                            MenuEntry::Quit => return Ok(()),
+                        },
+                        Screen::ScenarioPicker => {
+                            if let Some(mut game) = app.selected_scenario() {
+                                args.apply_overrides(&mut game);
+                                app.start_game(game);
+                            }
+                        }
+                        _ => {}
+                    },
+                    KeyCode::Char('q') | KeyCode::Esc if app.screen == Screen::Menu => {
+                        return Ok(())
+                    }
+                    KeyCode::Backspace | KeyCode::Char('q') | KeyCode::Esc => app.open_menu(),
+                    _ => {}
+                }
+                continue;
+            }
+
             action = match key.code {
                 KeyCode::Char('q') | KeyCode::Esc => Some(Inputs::Exit),
                 KeyCode::Left | KeyCode::Char('a') => Some(Inputs::Left),
@@ -73,15 +146,29 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<(
                 KeyCode::BackTab => Some(Inputs::DecreaseActionActivation),
                 KeyCode::Enter => Some(Inputs::ActivateOrGoToActions),
                 KeyCode::Char('c') => Some(Inputs::PassDay),
+                KeyCode::Char('x') => Some(Inputs::CancelBuild),
+                KeyCode::F(5) => Some(Inputs::SaveGame),
+                KeyCode::F(9) => Some(Inputs::LoadGame),
+                KeyCode::F(6) => Some(Inputs::CycleSaveSlot),
+                KeyCode::Char('u') => Some(Inputs::Undo),
+                KeyCode::Char('r') => Some(Inputs::Redo),
+                KeyCode::Char('n') => Some(Inputs::SwitchTab),
+                KeyCode::Char('t') => Some(Inputs::CycleStopOn),
+                KeyCode::Char('j') => Some(Inputs::ExportJson),
+                KeyCode::Char('m') => Some(Inputs::ExportMarkdown),
                 _ => None,
             };
 
-            if app.game_state.day < 0 {
-                action = None;
-                if key.code == KeyCode::Char('q') || key.code == KeyCode::Esc {
-                    action = Some(Inputs::Exit);
-                }
-            }
+            // the final day can still be undone and the
+            // report is only exported once the game is over
+            let over = app.engine.is_over();
+            action = match action {
+                Some(Inputs::Exit | Inputs::Undo) => action,
+                Some(Inputs::ExportJson | Inputs::ExportMarkdown) if over => action,
+                Some(Inputs::ExportJson | Inputs::ExportMarkdown) => None,
+                _ if over => None,
+                _ => action,
+            };
         }
 
         let Some(action) = action else {
@@ -89,17 +176,15 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<(
         };
 
         match action {
-            Inputs::Exit => return Ok(()),
+            Inputs::Exit => return app.autosave(),
             Inputs::Left => app.navigate(crate::app::Direction::Left),
             Inputs::Right => app.navigate(crate::app::Direction::Right),
             Inputs::Up => app.navigate(crate::app::Direction::Up),
             Inputs::Down => app.navigate(crate::app::Direction::Down),
+            Inputs::Back if app.tab != Tab::MainGame => app.open_tab(Tab::MainGame),
             Inputs::Back => {
-                if let Some(item) = app.game_state.items.get(&app.selected_item) {
-                    match item.category {
-                        ItemCategory::Resource => app.change_tab(Table::Resources),
-                        ItemCategory::Building => app.change_tab(Table::Buildings),
-                    }
+                if let Some(index) = app.selected_item_table() {
+                    app.change_tab(Table::Category(index));
                 }
             }
             Inputs::IncreaseActionActivation => match app.activation_amount {
@@ -114,16 +199,27 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<(
                 100 => app.activation_amount = 10,
                 _ => app.activation_amount = 100,
             },
-            Inputs::ActivateOrGoToActions => {
-                if app.selected_table != Table::Actions {
-                    app.change_tab(Table::Actions);
-                } else {
-                    app.call_selected_action();
+            Inputs::ActivateOrGoToActions => match app.tab {
+                Tab::NightMarket => app.trade_selected_offer(),
+                Tab::Research => app.research_selected(),
+                Tab::Progress => {}
+                Tab::MainGame if app.selected_table != Table::Actions => {
+                    app.change_tab(Table::Actions)
                 }
-            }
-            Inputs::PassDay => {
-                app.game_state.pass_day(app.activation_amount);
-            }
+                Tab::MainGame => app.call_selected_action(),
+            },
+            Inputs::PassDay => app.pass_days(),
+            Inputs::CancelBuild if app.tab == Tab::MainGame => app.cancel_selected_build(),
+            Inputs::CancelBuild => {}
+            Inputs::SaveGame => app.save_to_slot(),
+            Inputs::LoadGame => app.load_from_slot(),
+            Inputs::CycleSaveSlot => app.cycle_save_slot(),
+            Inputs::Undo => app.undo(),
+            Inputs::Redo => app.redo(),
+            Inputs::SwitchTab => app.switch_tab(),
+            Inputs::CycleStopOn => app.cycle_stop_on(),
+            Inputs::ExportJson => app.export_report(ReportFormat::Json),
+            Inputs::ExportMarkdown => app.export_report(ReportFormat::Markdown),
         }
     }
 }
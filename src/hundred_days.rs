@@ -1,5 +1,24 @@
 pub use serde::Deserialize;
 
 pub mod action;
+pub mod amount;
+pub mod construction;
+pub mod engine;
 pub mod game;
-pub mod item;
\ No newline at end of file
+pub mod high_scores;
+pub mod industries;
+pub mod item;
+pub mod market;
+pub mod night_market;
+pub mod objective;
+pub mod production;
+pub mod random_events;
+pub mod report;
+pub mod research;
+pub mod rng;
+pub mod snapshot;
+pub mod statistics;
+pub mod storage;
+pub mod undo;
+pub mod validation;
+pub mod valuation;
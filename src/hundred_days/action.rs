@@ -1,3 +1,6 @@
+use std::{collections::BTreeMap, fmt};
+
+use super::amount::{Amount, AmountOverflow};
 pub use super::game::GameState;
 
 pub mod active;
@@ -6,6 +9,206 @@ pub mod passive;
 pub trait Action {
     fn name(&self) -> &str;
     fn description(&self) -> String;
-    fn activate(&self, item_name: String, game: &mut GameState, amount: i32) -> String;
+    fn activate(
+        &self,
+        item_name: String,
+        game: &mut GameState,
+        amount: i32,
+    ) -> Result<Changes, ActionError>;
     fn max_activate(&self, item_name: String, game: &mut GameState) -> i32;
 }
+
+// difference in currency and item amounts
+// caused by an action or a passed day
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Changes {
+    pub currency: Amount,
+    pub items: BTreeMap<String, Amount>,
+}
+
+impl Changes {
+    // only used to show what happened, so
+    // differences too large to keep saturate
+    pub fn between(before: &GameState, after: &GameState) -> Self {
+        let items = after
+            .items
+            .iter()
+            .filter_map(|(name, item)| {
+                let before_amount = before.items.get(name).map_or(Amount::ZERO, |i| i.amount);
+                let delta = item.amount.saturating_sub(before_amount);
+
+                (delta != Amount::ZERO).then(|| (name.clone(), delta))
+            })
+            .collect();
+
+        Changes {
+            currency: after.currency.saturating_sub(before.currency),
+            items,
+        }
+    }
+
+    pub fn add_item(&mut self, name: &str, delta: Amount) -> Result<(), AmountOverflow> {
+        let total = self.items.entry(name.to_string()).or_default();
+        *total = total.checked_add(delta)?;
+        return Ok(());
+    }
+
+    pub fn add_currency(&mut self, delta: Amount) -> Result<(), AmountOverflow> {
+        self.currency = self.currency.checked_add(delta)?;
+        return Ok(());
+    }
+
+    // applies every change or, when one of them does
+    // not fit, none of them and returns the error
+    pub fn apply(&self, game: &mut GameState) -> Result<(), ActionError> {
+        let currency = game.currency.checked_add(self.currency)?;
+        let mut amounts = Vec::new();
+        for (name, delta) in &self.items {
+            amounts.push((name, item_amount(game, name)?.checked_add(*delta)?));
+        }
+
+        game.currency = currency;
+        for (name, amount) in amounts {
+            game.items.get_mut(name).unwrap().amount = amount;
+        }
+
+        return Ok(());
+    }
+}
+
+impl fmt::Display for Changes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if self.currency != Amount::ZERO {
+            parts.push(format!("$ {:+}", self.currency));
+        }
+        for (name, delta) in &self.items {
+            parts.push(format!("{name} {delta:+}"));
+        }
+
+        if parts.is_empty() {
+            write!(f, "no change")
+        } else {
+            write!(f, "{}", parts.join(", "))
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ActionError {
+    UnknownItem(String),
+    InsufficientFunds {
+        needed: Amount,
+        available: Amount,
+    },
+    InsufficientMaterials {
+        item: String,
+        needed: Amount,
+        available: Amount,
+    },
+    ExceedsMax {
+        requested: i32,
+        max: i32,
+    },
+    StorageFull {
+        item: String,
+        requested: Amount,
+        space: Amount,
+    },
+    UnknownOffer(usize),
+    // there is no build in that slot of the build queue
+    UnknownBuild(usize),
+    OutOfStock {
+        offer: String,
+        requested: i32,
+        remaining: i32,
+    },
+    // money or an item amount would get too large
+    Overflow,
+}
+
+impl fmt::Display for ActionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ActionError::UnknownItem(item) => write!(f, "Could not find item {item}"),
+            ActionError::InsufficientFunds { needed, available } => {
+                write!(f, "Not enough money: need {needed}, have {available}")
+            }
+            ActionError::InsufficientMaterials {
+                item,
+                needed,
+                available,
+            } => write!(f, "Not enough {item}: need {needed}, have {available}"),
+            ActionError::ExceedsMax { requested, max } => {
+                write!(
+                    f,
+                    "Requested {requested} but can only be called {max} more times"
+                )
+            }
+            ActionError::StorageFull {
+                item,
+                requested,
+                space,
+            } => write!(
+                f,
+                "Not enough storage for {item}: room for {space}, requested {requested}"
+            ),
+            ActionError::UnknownOffer(slot) => {
+                write!(f, "There is no offer {} on the night market", slot + 1)
+            }
+            ActionError::UnknownBuild(slot) => {
+                write!(f, "There is no build {} in the build queue", slot + 1)
+            }
+            ActionError::OutOfStock {
+                offer,
+                requested,
+                remaining,
+            } => write!(f, "{offer} has {remaining} left, requested {requested}"),
+            ActionError::Overflow => write!(f, "{AmountOverflow}"),
+        }
+    }
+}
+
+impl std::error::Error for ActionError {}
+
+impl From<AmountOverflow> for ActionError {
+    fn from(_: AmountOverflow) -> Self {
+        ActionError::Overflow
+    }
+}
+
+// amount of an item available to an action,
+// erroring if the scenario never defined it
+pub fn item_amount(game: &GameState, item_name: &str) -> Result<Amount, ActionError> {
+    return game
+        .items
+        .get(item_name)
+        .map(|item| item.amount)
+        .ok_or_else(|| ActionError::UnknownItem(item_name.to_string()));
+}
+
+// checks that every item in `costs` has at least
+// `cost * amount` available before anything is taken
+pub fn check_materials<'a>(
+    game: &GameState,
+    costs: impl IntoIterator<Item = (&'a String, &'a Amount)>,
+    amount: i32,
+) -> Result<(), ActionError> {
+    let mut costs = costs.into_iter().collect::<Vec<_>>();
+    costs.sort();
+
+    for (name, cost) in costs {
+        let available = item_amount(game, name)?;
+        let needed = cost.checked_mul(amount)?;
+
+        if available < needed {
+            return Err(ActionError::InsufficientMaterials {
+                item: name.clone(),
+                needed,
+                available,
+            });
+        }
+    }
+
+    return Ok(());
+}
@@ -1,14 +1,67 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-use super::{Action, GameState};
+use super::{check_materials, item_amount, Action, ActionError, Changes, GameState};
+use crate::hundred_days::{
+    amount::{Amount, AmountOverflow},
+    construction, market, storage,
+};
 
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum Active {
-    Buy { buy_price: i32 },
-    Sell { sell_price: i32 },
-    Construct { build_cost: HashMap<String, i32> },
-    Deconstruct { item_gain: HashMap<String, i32> },
+    Buy {
+        buy_price: Amount,
+    },
+    Sell {
+        sell_price: Amount,
+    },
+    Construct {
+        build_cost: HashMap<String, Amount>,
+        // days until built, 0 adds the buildings right away
+        #[serde(default)]
+        build_days: i32,
+    },
+    Deconstruct {
+        item_gain: HashMap<String, Amount>,
+    },
+}
+
+impl Active {
+    // description with buy and sell prices
+    // as the market currently has them
+    pub fn market_description(&self, item_name: &str, game: &GameState) -> String {
+        let price_line = |label: &str, base_price: Amount| {
+            let Ok(price) = market::current_price(game, item_name, base_price) else {
+                return format!("{label}: too high to pay");
+            };
+            if price == base_price {
+                format!("{label}: {price}")
+            } else {
+                format!("{label}: {price} (base {base_price})")
+            }
+        };
+
+        match self {
+            Active::Buy { buy_price } => price_line("Buy Price", *buy_price),
+            Active::Sell { sell_price } => price_line("Sell Price", *sell_price),
+            _ => self.description(),
+        }
+    }
+}
+
+// what deconstructing `amount` of the item takes and gives
+fn deconstruction(
+    item_name: &str,
+    item_gain: &HashMap<String, Amount>,
+    amount: i32,
+) -> Result<Changes, AmountOverflow> {
+    let mut changes = Changes::default();
+    changes.add_item(item_name, Amount::from(amount).checked_neg()?)?;
+    for (name, gain) in item_gain {
+        changes.add_item(name, gain.checked_mul(amount)?)?;
+    }
+
+    return Ok(changes);
 }
 
 impl Action for Active {
@@ -16,7 +69,7 @@ impl Action for Active {
         match self {
             Active::Buy { buy_price: _ } => "Buy",
             Active::Sell { sell_price: _ } => "Sell",
-            Active::Construct { build_cost: _ } => "Construct",
+            Active::Construct { .. } => "Construct",
             Active::Deconstruct { item_gain: _ } => "Deconstruct",
         }
     }
@@ -25,14 +78,21 @@ impl Action for Active {
         match self {
             Active::Buy { buy_price } => format!("Buy Price: {buy_price}"),
             Active::Sell { sell_price } => format!("Sell Price: {sell_price}"),
-            Active::Construct { build_cost } => {
-                format!(
+            Active::Construct {
+                build_cost,
+                build_days,
+            } => {
+                let mut description = format!(
                     "Construction Cost:\n{}",
                     build_cost
                         .iter()
                         .map(|(name, amount)| format!("{name}: {amount}\n"))
                         .collect::<String>()
-                )
+                );
+                if *build_days > 0 {
+                    description += &format!("Takes {build_days} days\n");
+                }
+                description
             }
             Active::Deconstruct { item_gain } => {
                 format!(
@@ -46,60 +106,103 @@ impl Action for Active {
         }
     }
 
-    fn activate(&self, item_name: String, game: &mut GameState, amount: i32) -> String {
-        if !game.items.contains_key(&item_name) {
-            return "Action could not find associated item".to_string();
-        };
+    fn activate(
+        &self,
+        item_name: String,
+        game: &mut GameState,
+        amount: i32,
+    ) -> Result<Changes, ActionError> {
+        let owned = item_amount(game, &item_name)?;
+        let units = Amount::from(amount);
 
-        let max_activates = self.max_activate(item_name.clone(), game);
-        if max_activates < amount {
-            return format!("Can only be called {max_activates} more times");
+        // only buying and building add to what has to be stored
+        if matches!(self, Active::Buy { .. } | Active::Construct { .. }) {
+            let space = storage::fitting(game, &item_name, units);
+            if space < units {
+                return Err(ActionError::StorageFull {
+                    item: item_name,
+                    requested: units,
+                    space,
+                });
+            }
         }
 
+        let mut changes = Changes::default();
         match self {
             Active::Buy { buy_price } => {
-                game.items.get_mut(&item_name).unwrap().amount += amount;
-                game.currency -= buy_price * amount;
+                let cost = market::quote(game, &item_name, *buy_price, amount, true)?;
+                if cost > game.currency {
+                    return Err(ActionError::InsufficientFunds {
+                        needed: cost,
+                        available: game.currency,
+                    });
+                }
 
-                return format!("Purchased {amount} {item_name} for {}", buy_price * amount);
+                changes.add_item(&item_name, units)?;
+                changes.add_currency(cost.checked_neg()?)?;
+                changes.apply(game)?;
+                market::record_trade(game, &item_name, *buy_price, amount, true);
             }
             Active::Sell { sell_price } => {
-                game.items.get_mut(&item_name).unwrap().amount -= amount;
-                game.currency += sell_price * amount;
+                if units > owned {
+                    return Err(ActionError::InsufficientMaterials {
+                        item: item_name,
+                        needed: units,
+                        available: owned,
+                    });
+                }
+
+                let earnings = market::quote(game, &item_name, *sell_price, amount, false)?;
 
-                return format!("Sold {amount} {item_name} for {}", sell_price * amount);
+                changes.add_item(&item_name, units.checked_neg()?)?;
+                changes.add_currency(earnings)?;
+                changes.apply(game)?;
+                market::record_trade(game, &item_name, *sell_price, amount, false);
             }
-            Active::Construct { build_cost } => {
-                game.items.get_mut(&item_name).unwrap().amount += amount;
+            Active::Construct {
+                build_cost,
+                build_days,
+            } => {
+                check_materials(game, build_cost, amount)?;
+
+                let max_activates = self.max_activate(item_name.clone(), game);
+                if max_activates < amount {
+                    return Err(ActionError::ExceedsMax {
+                        requested: amount,
+                        max: max_activates,
+                    });
+                }
 
+                // buildings that take time are added once
+                // the build queue is done with them
+                if *build_days <= 0 {
+                    changes.add_item(&item_name, units)?;
+                }
                 for (name, cost) in build_cost {
-                    game.items.get_mut(name).unwrap().amount -= cost * amount;
+                    changes.add_item(name, cost.checked_mul(amount)?.checked_neg()?)?;
                 }
+                changes.apply(game)?;
 
-                return format!(
-                    "Constructed {amount} {item_name} for: {{ {}}}",
-                    build_cost
-                        .iter()
-                        .map(|(s, i)| { format!("{s}: {i} ") })
-                        .collect::<String>()
-                );
+                if *build_days > 0 {
+                    construction::queue(game, &item_name, amount, build_cost, *build_days);
+                }
             }
             Active::Deconstruct { item_gain } => {
-                game.items.get_mut(&item_name).unwrap().amount -= amount;
-
-                for (name, gain) in item_gain {
-                    game.items.get_mut(name).unwrap().amount += gain * amount;
+                if units > owned {
+                    return Err(ActionError::InsufficientMaterials {
+                        item: item_name,
+                        needed: units,
+                        available: owned,
+                    });
                 }
 
-                return format!(
-                    "Deconstructed {amount} {item_name} for: {{ {}}}",
-                    item_gain
-                        .iter()
-                        .map(|(s, i)| { format!("{s}: {i} ") })
-                        .collect::<String>()
-                );
+                changes = deconstruction(&item_name, item_gain, amount)?;
+                storage::check(game, &changes)?;
+                changes.apply(game)?;
             }
         }
+
+        return Ok(changes);
     }
 
     fn max_activate(&self, item_name: String, game: &mut GameState) -> i32 {
@@ -109,20 +212,30 @@ impl Action for Active {
 
         match self {
             Active::Buy { buy_price } => {
-                return game.currency / buy_price;
+                let affordable = market::affordable(game, &item_name, *buy_price, game.currency);
+                return storage::fitting(game, &item_name, affordable.into()).count();
             }
             Active::Sell { sell_price: _ } => {
-                return game.items.get(&item_name).unwrap().amount;
+                return game.items.get(&item_name).unwrap().amount.count();
             }
-            Active::Construct { build_cost } => {
-                return build_cost
+            Active::Construct { build_cost, .. } => {
+                let buildable = build_cost
                     .iter()
-                    .map(|(item_name, cost)| game.items.get(item_name).unwrap().amount / cost)
+                    .map(|(item_name, cost)| game.items.get(item_name).unwrap().amount.times(*cost))
                     .min()
                     .unwrap_or(0);
+                return storage::fitting(game, &item_name, buildable.into()).count();
             }
-            Active::Deconstruct { item_gain: _ } => {
-                return game.items.get(&item_name).unwrap().amount;
+            Active::Deconstruct { item_gain } => {
+                let storable = deconstruction(&item_name, item_gain, 1)
+                    .map_or(0, |unit| storage::times_fitting(game, &unit));
+                return game
+                    .items
+                    .get(&item_name)
+                    .unwrap()
+                    .amount
+                    .count()
+                    .min(storable);
             }
         }
     }
@@ -1,87 +1,308 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-use super::{Action, GameState};
+use super::{check_materials, item_amount, Action, ActionError, Changes, GameState};
+use crate::hundred_days::{
+    amount::{Amount, AmountOverflow},
+    industries,
+    storage::{self, Overflow},
+};
 
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+// passives run every day, `amount` times for
+// every unit of the item that is owned
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum Passive {
     Produce {
-        item_production: HashMap<String, i32>,
+        item_production: HashMap<String, Amount>,
+        // what happens to what does not fit in storage
+        #[serde(default)]
+        overflow: Overflow,
     },
+    // upkeep, paid before anything is produced
     Reduce {
-        item_reduction: HashMap<String, i32>,
+        #[serde(default)]
+        item_reduction: HashMap<String, Amount>,
+        #[serde(default)]
+        currency_reduction: Amount,
+        #[serde(default)]
+        shortfall: Shortfall,
     },
+    // a recipe, each run takes all of `inputs` and gives all
+    // of `outputs`, it never runs with only part of its inputs
+    Convert {
+        inputs: HashMap<String, Amount>,
+        outputs: HashMap<String, Amount>,
+    },
+}
+
+// what happens on a day the
+// upkeep of an item is not paid
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum Shortfall {
+    // nothing is paid and the item produces nothing that day
+    #[default]
+    Halt,
+    // nothing is paid, nothing is produced and one unit of the item is lost
+    Decay,
+    // currency is paid even when it goes below zero,
+    // missing items still halt production
+    Debt,
+}
+
+// what running a recipe `runs` times takes and gives
+fn recipe(
+    inputs: &HashMap<String, Amount>,
+    outputs: &HashMap<String, Amount>,
+    runs: i32,
+) -> Result<Changes, AmountOverflow> {
+    let mut changes = Changes::default();
+    for (name, input) in inputs {
+        changes.add_item(name, input.checked_mul(runs)?.checked_neg()?)?;
+    }
+    for (name, output) in outputs {
+        changes.add_item(name, output.checked_mul(runs)?)?;
+    }
+
+    return Ok(changes);
+}
+
+// how often a recipe can run with the `inputs`
+// owned and the storage left for its `outputs`
+fn convertible(
+    game: &GameState,
+    inputs: &HashMap<String, Amount>,
+    outputs: &HashMap<String, Amount>,
+) -> i32 {
+    let storable = recipe(inputs, outputs, 1).map_or(0, |run| storage::times_fitting(game, &run));
+
+    return inputs
+        .iter()
+        .map(|(item_name, input)| {
+            game.items
+                .get(item_name)
+                .map_or(0, |item| item.amount.times(*input))
+        })
+        .min()
+        .unwrap_or(i32::MAX)
+        .min(storable);
+}
+
+// how often `production` can be added to
+// what is owned before it would overflow
+fn producible(game: &GameState, item_name: &str, production: &HashMap<String, Amount>) -> i32 {
+    // rounded up, so runs never
+    // add more than counted here
+    let share = 1.0 + industries::production_bonus(game, item_name).max(0.0);
+    return production
+        .iter()
+        .filter(|(_, produced)| produced.is_positive())
+        .map(|(name, produced)| {
+            let Ok(per_run) = Amount::from_f64((produced.as_f64() * share).ceil()) else {
+                return 0;
+            };
+            let owned = game
+                .items
+                .get(name)
+                .map_or(Amount::ZERO, |item| item.amount);
+            Amount::MAX.saturating_sub(owned).times(per_run)
+        })
+        .min()
+        .unwrap_or(i32::MAX);
+}
+
+impl Passive {
+    // description with how often a conversion can run with
+    // the items owned right now, or the synergies of production
+    pub fn current_description(&self, item_name: &str, game: &GameState) -> String {
+        let mut description = self.description();
+
+        match self {
+            Passive::Convert { inputs, outputs } => {
+                let owned = game
+                    .items
+                    .get(item_name)
+                    .map_or(Amount::ZERO, |item| item.amount);
+                let runs = convertible(game, inputs, outputs).min(owned.count());
+
+                description += &format!("Runs: {runs} of {owned}\n");
+            }
+            Passive::Produce {
+                item_production, ..
+            } => {
+                let mut full = item_production
+                    .keys()
+                    .filter(|name| storage::space(game, name) == Some(Amount::ZERO))
+                    .cloned()
+                    .collect::<Vec<String>>();
+                full.sort();
+                for name in full {
+                    description += &format!("{name} storage is full\n");
+                }
+
+                for synergy in industries::synergies(game, item_name) {
+                    let status = if synergy.is_active(game) {
+                        "active".to_string()
+                    } else {
+                        let mut requires = synergy
+                            .requires
+                            .iter()
+                            .map(|(name, amount)| format!("{amount} {name}"))
+                            .collect::<Vec<String>>();
+                        requires.sort();
+                        format!("needs {}", requires.join(", "))
+                    };
+
+                    description += &format!(
+                        "{} {:+}%: {status}\n",
+                        synergy.name,
+                        (synergy.production_bonus * 100.0).round()
+                    );
+                }
+            }
+            Passive::Reduce { .. } => {}
+        }
+
+        return description;
+    }
 }
 
 impl Action for Passive {
     fn name(&self) -> &str {
         match self {
-            Passive::Produce { item_production: _ } => "Produce",
-            Passive::Reduce { item_reduction: _ } => "Reduce",
+            Passive::Produce { .. } => "Produce",
+            Passive::Reduce { .. } => "Reduce",
+            Passive::Convert { .. } => "Convert",
         }
     }
 
     fn description(&self) -> String {
         match self {
-            Passive::Produce { item_production } => {
-                format!(
+            Passive::Produce {
+                item_production,
+                overflow,
+            } => {
+                let mut description = format!(
                     "Produces daily:\n{}",
                     item_production
                         .iter()
                         .map(|(name, amount)| format!("{name}: {amount}\n"))
                         .collect::<String>()
-                )
+                );
+                if let Overflow::Sell { discount } = overflow {
+                    description += &format!(
+                        "If storage is full: sold at {}% off\n",
+                        (discount * 100.0).round()
+                    );
+                }
+
+                description
             }
-            Passive::Reduce { item_reduction } => {
-                format!(
-                    "Reduces daily:\n{}",
+            Passive::Reduce {
+                item_reduction,
+                currency_reduction,
+                shortfall,
+            } => {
+                let mut description = format!(
+                    "Upkeep daily:\n{}",
                     item_reduction
                         .iter()
                         .map(|(name, amount)| format!("{name}: {amount}\n"))
                         .collect::<String>()
-                )
+                );
+                if currency_reduction.is_positive() {
+                    description += &format!("Money: {currency_reduction}\n");
+                }
+                description += match shortfall {
+                    Shortfall::Halt => "If unpaid: production halts\n",
+                    Shortfall::Decay => "If unpaid: production halts, one falls apart\n",
+                    Shortfall::Debt => "If unpaid: money goes into debt\n",
+                };
+
+                description
+            }
+            Passive::Convert { inputs, outputs } => {
+                let list = |amounts: &HashMap<String, Amount>| {
+                    let mut amounts = amounts
+                        .iter()
+                        .map(|(name, amount)| format!("{name}: {amount}\n"))
+                        .collect::<Vec<String>>();
+                    amounts.sort();
+                    amounts.concat()
+                };
+
+                format!("Converts daily:\n{}into:\n{}", list(inputs), list(outputs))
             }
         }
     }
 
-    fn activate(&self, item_name: String, game: &mut GameState, amount: i32) -> String {
-        if !game.items.contains_key(&item_name) {
-            return "Action could not find associated item".to_string();
-        };
-
-        let max_activates = self.max_activate(item_name.clone(), game);
-        if max_activates < amount {
-            return format!("Can only be called {max_activates} more times");
-        };
+    fn activate(
+        &self,
+        item_name: String,
+        game: &mut GameState,
+        amount: i32,
+    ) -> Result<Changes, ActionError> {
+        item_amount(game, &item_name)?;
 
+        let mut changes = Changes::default();
         match self {
-            Passive::Produce { item_production } => {
+            Passive::Produce {
+                item_production,
+                overflow,
+            } => {
+                for name in item_production.keys() {
+                    item_amount(game, name)?;
+                }
+
+                let bonus = industries::production_bonus(game, &item_name);
+                // checked up front so a product that would
+                // overflow keeps the others from being stored
+                let mut produced = Vec::new();
                 for (name, production) in item_production {
-                    game.items.get_mut(name).unwrap().amount += production * amount;
+                    let amount = industries::with_bonus(production.checked_mul(amount)?, bonus)?;
+                    item_amount(game, name)?.checked_add(amount)?;
+                    produced.push((name, amount));
                 }
+                // products sharing storage get room
+                // in name order, the same on every run
+                produced.sort();
 
-                return format!(
-                    "Produced {{ {}}}",
-                    item_production
-                        .iter()
-                        .map(|(s, i)| { format!("{s}: {i} ") })
-                        .collect::<String>()
-                );
+                for (name, amount) in produced {
+                    storage::store(game, name, amount, *overflow, &mut changes)?;
+                }
             }
-            Passive::Reduce { item_reduction } => {
+            Passive::Reduce {
+                item_reduction,
+                currency_reduction,
+                shortfall,
+            } => {
+                let cost = currency_reduction.checked_mul(amount)?;
+                if cost > game.currency && *shortfall != Shortfall::Debt {
+                    return Err(ActionError::InsufficientFunds {
+                        needed: cost,
+                        available: game.currency,
+                    });
+                }
+                check_materials(game, item_reduction, amount)?;
+
+                changes.add_currency(cost.checked_neg()?)?;
                 for (name, reduction) in item_reduction {
-                    game.items.get_mut(name).unwrap().amount -= reduction * amount;
+                    changes.add_item(name, reduction.checked_mul(amount)?.checked_neg()?)?;
+                }
+                changes.apply(game)?;
+            }
+            Passive::Convert { inputs, outputs } => {
+                check_materials(game, inputs, amount)?;
+                for name in outputs.keys() {
+                    item_amount(game, name)?;
                 }
 
-                return format!(
-                    "Reduced {{ {}}}",
-                    item_reduction
-                        .iter()
-                        .map(|(s, i)| { format!("{s}: {i} ") })
-                        .collect::<String>()
-                );
+                changes = recipe(inputs, outputs, amount)?;
+                storage::check(game, &changes)?;
+                changes.apply(game)?;
             }
         }
+
+        return Ok(changes);
     }
 
     fn max_activate(&self, item_name: String, game: &mut GameState) -> i32 {
@@ -90,15 +311,31 @@ impl Action for Passive {
         };
 
         match self {
-            Passive::Produce { item_production: _ } => {
-                return i32::MAX;
+            Passive::Produce {
+                item_production, ..
+            } => {
+                return producible(game, &item_name, item_production);
             }
-            Passive::Reduce { item_reduction } => {
+            Passive::Reduce {
+                item_reduction,
+                currency_reduction,
+                shortfall: _,
+            } => {
+                let affordable = if currency_reduction.is_positive() {
+                    game.currency.times(*currency_reduction)
+                } else {
+                    i32::MAX
+                };
+
                 return item_reduction
                     .iter()
-                    .map(|(item_name, cost)| game.items.get(item_name).unwrap().amount / cost)
+                    .map(|(item_name, cost)| game.items.get(item_name).unwrap().amount.times(*cost))
                     .min()
-                    .unwrap_or(0);
+                    .unwrap_or(i32::MAX)
+                    .min(affordable);
+            }
+            Passive::Convert { inputs, outputs } => {
+                return convertible(game, inputs, outputs);
             }
         }
     }
@@ -0,0 +1,179 @@
+use std::{fmt, iter::Sum};
+
+use serde::{Deserialize, Serialize};
+
+// money or a number of items. Everything that changes the
+// game goes through the checked operations, which fail
+// instead of wrapping around or panicking
+#[derive(
+    Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default,
+)]
+#[serde(transparent)]
+pub struct Amount(i64);
+
+// an operation on amounts would not fit in an `Amount`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AmountOverflow;
+
+impl Amount {
+    pub const ZERO: Amount = Amount(0);
+    pub const MAX: Amount = Amount(i64::MAX);
+
+    pub const fn new(value: i64) -> Self {
+        Amount(value)
+    }
+
+    pub fn get(self) -> i64 {
+        return self.0;
+    }
+
+    pub fn checked_add(self, other: Amount) -> Result<Amount, AmountOverflow> {
+        return self
+            .0
+            .checked_add(other.0)
+            .map(Amount)
+            .ok_or(AmountOverflow);
+    }
+
+    pub fn checked_sub(self, other: Amount) -> Result<Amount, AmountOverflow> {
+        return self
+            .0
+            .checked_sub(other.0)
+            .map(Amount)
+            .ok_or(AmountOverflow);
+    }
+
+    // `times` units of this amount each, such
+    // as the price of buying several items
+    pub fn checked_mul(self, times: i32) -> Result<Amount, AmountOverflow> {
+        return self
+            .0
+            .checked_mul(times as i64)
+            .map(Amount)
+            .ok_or(AmountOverflow);
+    }
+
+    pub fn checked_neg(self) -> Result<Amount, AmountOverflow> {
+        return self.0.checked_neg().map(Amount).ok_or(AmountOverflow);
+    }
+
+    // how many times `unit` fits, 0 for units that are not positive
+    pub fn times(self, unit: Amount) -> i32 {
+        if unit.0 <= 0 {
+            return 0;
+        }
+
+        return (self.0.max(0) / unit.0).min(i32::MAX as i64) as i32;
+    }
+
+    // rounded down, anything that is not a finite
+    // number in range is an overflow as well
+    pub fn from_f64(value: f64) -> Result<Amount, AmountOverflow> {
+        let value = value.floor();
+        if !value.is_finite() || value < i64::MIN as f64 || value >= i64::MAX as f64 {
+            return Err(AmountOverflow);
+        }
+
+        return Ok(Amount(value as i64));
+    }
+
+    // as a number of times to run an action,
+    // which is never negative
+    pub fn count(self) -> i32 {
+        return self.0.clamp(0, i32::MAX as i64) as i32;
+    }
+
+    pub fn as_f64(self) -> f64 {
+        return self.0 as f64;
+    }
+
+    // for totals that are only shown, such as
+    // statistics and charts, never for the game itself
+    pub fn saturating_add(self, other: Amount) -> Amount {
+        return Amount(self.0.saturating_add(other.0));
+    }
+
+    pub fn saturating_sub(self, other: Amount) -> Amount {
+        return Amount(self.0.saturating_sub(other.0));
+    }
+
+    pub fn is_negative(self) -> bool {
+        return self.0 < 0;
+    }
+
+    pub fn is_positive(self) -> bool {
+        return self.0 > 0;
+    }
+}
+
+// adds up amounts, failing on the first overflow
+pub fn checked_sum(amounts: impl IntoIterator<Item = Amount>) -> Result<Amount, AmountOverflow> {
+    return amounts
+        .into_iter()
+        .try_fold(Amount::ZERO, |total, amount| total.checked_add(amount));
+}
+
+// saturates like `Amount::saturating_add`
+impl Sum for Amount {
+    fn sum<I: Iterator<Item = Amount>>(amounts: I) -> Self {
+        return amounts.fold(Amount::ZERO, Amount::saturating_add);
+    }
+}
+
+impl From<i32> for Amount {
+    fn from(value: i32) -> Self {
+        Amount(value as i64)
+    }
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl fmt::Display for AmountOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "The amount is too large")
+    }
+}
+
+impl std::error::Error for AmountOverflow {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checked_operations_fail_instead_of_wrapping() {
+        let one = Amount::new(1);
+
+        assert_eq!(Amount::MAX.checked_add(one), Err(AmountOverflow));
+        assert_eq!(Amount::new(i64::MIN).checked_sub(one), Err(AmountOverflow));
+        assert_eq!(Amount::MAX.checked_mul(2), Err(AmountOverflow));
+        assert_eq!(Amount::new(i64::MIN).checked_neg(), Err(AmountOverflow));
+        assert_eq!(Amount::new(3).checked_mul(-2), Ok(Amount::new(-6)));
+        assert_eq!(checked_sum([Amount::MAX, one]), Err(AmountOverflow));
+    }
+
+    #[test]
+    fn from_f64_rejects_what_does_not_fit() {
+        assert_eq!(Amount::from_f64(2.9), Ok(Amount::new(2)));
+        assert_eq!(Amount::from_f64(f64::NAN), Err(AmountOverflow));
+        assert_eq!(Amount::from_f64(f64::INFINITY), Err(AmountOverflow));
+        assert_eq!(Amount::from_f64(1e19), Err(AmountOverflow));
+    }
+
+    #[test]
+    fn counts_stay_in_range() {
+        assert_eq!(Amount::MAX.count(), i32::MAX);
+        assert_eq!(Amount::new(-5).count(), 0);
+        assert_eq!(Amount::new(7).times(Amount::new(2)), 3);
+        assert_eq!(Amount::new(7).times(Amount::ZERO), 0);
+        assert_eq!(Amount::MAX.times(Amount::new(1)), i32::MAX);
+        assert_eq!(
+            [Amount::MAX, Amount::MAX].into_iter().sum::<Amount>(),
+            Amount::MAX
+        );
+    }
+}
@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    action::{ActionError, Changes},
+    amount::{Amount, AmountOverflow},
+    game::{DayEvent, GameState},
+};
+
+// share of the build cost given back when a build
+// that was already worked on is cancelled
+pub const CANCEL_REFUND: f64 = 0.5;
+
+// buildings paid for but not finished yet, worked
+// on in the order they were queued
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Build {
+    pub item: String,
+    pub amount: i32,
+    // `build_days` of the construct action
+    pub days: i32,
+    pub days_left: i32,
+    // what was paid for each unit
+    pub build_cost: HashMap<String, Amount>,
+}
+
+impl Build {
+    pub fn is_started(&self) -> bool {
+        return self.days_left < self.days;
+    }
+
+    // everything is given back for builds that were
+    // never worked on, `CANCEL_REFUND` of it otherwise
+    pub fn refund(&self) -> Result<HashMap<String, Amount>, AmountOverflow> {
+        return self
+            .build_cost
+            .iter()
+            .map(|(name, cost)| {
+                let paid = cost.checked_mul(self.amount)?;
+                let refund = if self.is_started() {
+                    Amount::from_f64(paid.as_f64() * CANCEL_REFUND)?
+                } else {
+                    paid
+                };
+                Ok((name.clone(), refund))
+            })
+            .collect();
+    }
+}
+
+// puts a paid for build at the end of the queue
+pub fn queue(
+    game: &mut GameState,
+    item: &str,
+    amount: i32,
+    build_cost: &HashMap<String, Amount>,
+    days: i32,
+) {
+    game.build_queue.push(Build {
+        item: item.to_string(),
+        amount,
+        days,
+        days_left: days,
+        build_cost: build_cost.clone(),
+    });
+}
+
+// whether builders are left over for the build in `slot`
+// once the builds queued before it are worked on
+pub fn is_worked_on(game: &GameState, slot: usize) -> bool {
+    let Some(builders) = game.builders else {
+        return slot < game.build_queue.len();
+    };
+
+    let busy = game
+        .build_queue
+        .iter()
+        .take(slot)
+        .map(|build| Amount::from(build.amount))
+        .sum::<Amount>();
+    return slot < game.build_queue.len() && busy < Amount::from(builders);
+}
+
+// works a day on every build there are builders for, one builder
+// per unit, and adds the buildings that were finished
+pub fn pass_day(game: &mut GameState) -> Vec<DayEvent> {
+    let mut builders = game.builders.unwrap_or(i32::MAX);
+    let mut slot = 0;
+    while slot < game.build_queue.len() && builders > 0 {
+        // builders only get to part of the build, the
+        // rest waits as a build of its own
+        if game.build_queue[slot].amount > builders {
+            let mut waiting = game.build_queue[slot].clone();
+            waiting.amount -= builders;
+            game.build_queue[slot].amount = builders;
+            game.build_queue.insert(slot + 1, waiting);
+        }
+
+        let build = &mut game.build_queue[slot];
+        builders -= build.amount;
+        build.days_left -= 1;
+        slot += 1;
+    }
+
+    let mut events = Vec::new();
+    let (finished, building): (Vec<Build>, Vec<Build>) = std::mem::take(&mut game.build_queue)
+        .into_iter()
+        .partition(|build| build.days_left <= 0);
+    game.build_queue = building;
+
+    for build in finished {
+        if let Some(item) = game.items.get_mut(&build.item) {
+            // finished buildings that cannot be counted
+            // wait in the queue until there is room
+            let Ok(amount) = item.amount.checked_add(Amount::from(build.amount)) else {
+                game.build_queue.push(build);
+                continue;
+            };
+            item.amount = amount;
+        }
+
+        events.push(DayEvent::Constructed {
+            day: game.day,
+            item: build.item,
+            amount: build.amount,
+        });
+    }
+
+    return events;
+}
+
+// takes the build in `slot` off the queue,
+// giving back what `Build::refund` allows
+pub fn cancel(game: &mut GameState, slot: usize) -> Result<(Build, Changes), ActionError> {
+    if slot >= game.build_queue.len() {
+        return Err(ActionError::UnknownBuild(slot));
+    }
+
+    let mut changes = Changes::default();
+    for (name, refund) in game.build_queue[slot].refund()? {
+        if game.items.contains_key(&name) {
+            changes.add_item(&name, refund)?;
+        }
+    }
+    changes.apply(game)?;
+
+    let build = game.build_queue.remove(slot);
+    return Ok((build, changes));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hundred_days::engine::Engine;
+
+    fn cost(wood: i64) -> HashMap<String, Amount> {
+        return HashMap::from([("Wood".to_string(), Amount::new(wood))]);
+    }
+
+    fn queued(game: &GameState) -> Vec<(i32, i32)> {
+        return game
+            .build_queue
+            .iter()
+            .map(|build| (build.amount, build.days_left))
+            .collect();
+    }
+
+    #[test]
+    fn builds_beyond_the_builders_wait_as_builds_of_their_own() {
+        let mut game = GameState::generate_default();
+        game.builders = Some(2);
+        let sawmills = game.items["Sawmill"].amount;
+        queue(&mut game, "Sawmill", 3, &cost(15), 2);
+
+        assert!(pass_day(&mut game).is_empty());
+        assert_eq!(queued(&game), [(2, 1), (1, 2)]);
+        assert!(is_worked_on(&game, 0));
+        assert!(!is_worked_on(&game, 1));
+
+        let events = pass_day(&mut game);
+        assert!(matches!(
+            events.as_slice(),
+            [DayEvent::Constructed { amount: 2, .. }]
+        ));
+        assert_eq!(queued(&game), [(1, 2)]);
+        assert_eq!(
+            game.items["Sawmill"].amount,
+            sawmills.checked_add(Amount::new(2)).unwrap()
+        );
+    }
+
+    #[test]
+    fn cancelled_builds_are_refunded_in_full_until_worked_on() {
+        let mut game = GameState::generate_default();
+        game.builders = Some(1);
+        queue(&mut game, "Sawmill", 1, &cost(15), 3);
+        queue(&mut game, "Mine", 1, &cost(30), 3);
+        pass_day(&mut game);
+
+        let (build, changes) = cancel(&mut game, 1).unwrap();
+        assert_eq!(build.item, "Mine");
+        assert_eq!(changes.items["Wood"], Amount::new(30));
+
+        // half of 15, rounded down
+        let (_, changes) = cancel(&mut game, 0).unwrap();
+        assert_eq!(changes.items["Wood"], Amount::new(7));
+
+        assert!(matches!(
+            cancel(&mut game, 0),
+            Err(ActionError::UnknownBuild(0))
+        ));
+    }
+
+    #[test]
+    fn cancelled_builds_are_not_counted_as_constructed() {
+        let mut engine = Engine::new(GameState::generate_default());
+        engine.execute("Construct Sawmill 2".parse().unwrap());
+        engine.execute("cancel 1".parse().unwrap());
+
+        let constructed = &engine.state.statistics.constructed;
+        assert_eq!(
+            constructed.get("Sawmill").copied().unwrap_or_default(),
+            Amount::ZERO
+        );
+    }
+}
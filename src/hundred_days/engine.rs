@@ -0,0 +1,652 @@
+use std::{fmt, str::FromStr};
+
+use super::{
+    action::{Action, ActionError, Changes},
+    amount::Amount,
+    construction,
+    game::{GameState, PassedDays, StopOn},
+    night_market,
+    research::{self, ResearchError},
+    undo::UndoStack,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    // activate the active action named `action`
+    // on `item`, `amount` times
+    Activate {
+        item: String,
+        action: String,
+        amount: i32,
+    },
+    // pass up to `days` days, one at a time
+    PassDays {
+        days: i32,
+        stop_on: StopOn,
+    },
+    // take the night market offer in `slot`
+    Trade {
+        slot: usize,
+        amount: i32,
+    },
+    // cancel the build in `slot` of the build queue
+    Cancel {
+        slot: usize,
+    },
+    // start researching the tech named `tech`
+    Research {
+        tech: String,
+    },
+    Undo,
+    Redo,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rejection {
+    GameOver,
+    InvalidAmount(i32),
+    UnknownAction { item: String, action: String },
+    // the item is not unlocked by research yet
+    Locked(String),
+    Action(ActionError),
+    Research(ResearchError),
+    // the scenario is played in hardcore mode
+    UndoDisabled,
+    NothingToUndo,
+    NothingToRedo,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    ActionApplied {
+        item: String,
+        action: String,
+        amount: i32,
+        changes: Changes,
+    },
+    CommandRejected {
+        command: Command,
+        reason: Rejection,
+    },
+    DaysPassed {
+        requested: i32,
+        changes: Changes,
+        // what happened on each of the days
+        passed: PassedDays,
+    },
+    Traded {
+        offer: String,
+        amount: i32,
+        changes: Changes,
+    },
+    // `changes` is the refund
+    BuildCancelled {
+        item: String,
+        amount: i32,
+        changes: Changes,
+    },
+    // `days` is 0 for techs that were finished right away
+    ResearchStarted {
+        tech: String,
+        days: i32,
+        changes: Changes,
+    },
+    Undone {
+        changes: Changes,
+    },
+    Redone {
+        changes: Changes,
+    },
+    GameEnded {
+        net_worth: Amount,
+    },
+}
+
+pub struct Engine {
+    pub state: GameState,
+    undo_stack: UndoStack,
+}
+
+impl Engine {
+    pub fn new(state: GameState) -> Self {
+        Engine {
+            state,
+            undo_stack: UndoStack::default(),
+        }
+    }
+
+    pub fn is_over(&self) -> bool {
+        return self.state.day < 0;
+    }
+
+    pub fn undo_enabled(&self) -> bool {
+        return !self.state.hardcore;
+    }
+
+    pub fn can_undo(&self) -> bool {
+        return self.undo_enabled() && self.undo_stack.can_undo();
+    }
+
+    pub fn can_redo(&self) -> bool {
+        return self.undo_enabled() && self.undo_stack.can_redo();
+    }
+
+    pub fn execute(&mut self, command: Command) -> Vec<Event> {
+        let was_over = self.is_over();
+        let before = self.state.clone();
+
+        let result = match &command {
+            Command::Undo => self.undo(),
+            Command::Redo => self.redo(),
+            _ if was_over => Err(Rejection::GameOver),
+            Command::Activate {
+                item,
+                action,
+                amount,
+            } => self.activate(item, action, *amount),
+            Command::PassDays { days, stop_on } => self.pass_days(*days, *stop_on),
+            Command::Trade { slot, amount } => self.trade(*slot, *amount),
+            Command::Cancel { slot } => self.cancel(*slot),
+            Command::Research { tech } => self.research(tech),
+        };
+
+        let mut events = match result {
+            Ok(event) => vec![event],
+            Err(reason) => vec![Event::CommandRejected { command, reason }],
+        };
+
+        let changed_game = matches!(
+            events.first(),
+            Some(
+                Event::ActionApplied { .. }
+                    | Event::DaysPassed { .. }
+                    | Event::Traded { .. }
+                    | Event::BuildCancelled { .. }
+                    | Event::ResearchStarted { .. }
+            )
+        );
+        if changed_game && self.undo_enabled() {
+            self.undo_stack.record(before);
+        }
+
+        if !was_over && self.is_over() {
+            events.push(Event::GameEnded {
+                net_worth: self.state.net_worth(),
+            });
+        }
+
+        return events;
+    }
+
+    fn activate(
+        &mut self,
+        item_name: &str,
+        action_name: &str,
+        amount: i32,
+    ) -> Result<Event, Rejection> {
+        if amount <= 0 {
+            return Err(Rejection::InvalidAmount(amount));
+        }
+
+        let Some(item) = self.state.items.get(item_name) else {
+            return Err(Rejection::Action(ActionError::UnknownItem(
+                item_name.to_string(),
+            )));
+        };
+        if research::is_locked(&self.state, item_name) {
+            return Err(Rejection::Locked(item_name.to_string()));
+        }
+
+        let Some(action) = item
+            .actions_active
+            .iter()
+            .find(|a| a.name() == action_name)
+            .cloned()
+        else {
+            return Err(Rejection::UnknownAction {
+                item: item_name.to_string(),
+                action: action_name.to_string(),
+            });
+        };
+
+        let changes = action
+            .activate(item_name.to_string(), &mut self.state, amount)
+            .map_err(Rejection::Action)?;
+        self.state
+            .statistics
+            .record_action(&action, item_name, amount, &changes);
+
+        return Ok(Event::ActionApplied {
+            item: item_name.to_string(),
+            action: action_name.to_string(),
+            amount,
+            changes,
+        });
+    }
+
+    fn trade(&mut self, slot: usize, amount: i32) -> Result<Event, Rejection> {
+        if amount <= 0 {
+            return Err(Rejection::InvalidAmount(amount));
+        }
+
+        let changes =
+            night_market::trade(&mut self.state, slot, amount).map_err(Rejection::Action)?;
+        self.state.statistics.record_trade(amount, &changes);
+        let offer = self
+            .state
+            .night_market
+            .as_ref()
+            .and_then(|night_market| night_market.stocked_offer(slot))
+            .map(|(offer, _)| offer.name.clone())
+            .unwrap_or_default();
+
+        return Ok(Event::Traded {
+            offer,
+            amount,
+            changes,
+        });
+    }
+
+    fn cancel(&mut self, slot: usize) -> Result<Event, Rejection> {
+        let (build, changes) =
+            construction::cancel(&mut self.state, slot).map_err(Rejection::Action)?;
+        self.state
+            .statistics
+            .record_cancel(&build.item, build.amount);
+
+        return Ok(Event::BuildCancelled {
+            item: build.item,
+            amount: build.amount,
+            changes,
+        });
+    }
+
+    fn research(&mut self, tech: &str) -> Result<Event, Rejection> {
+        let changes = research::start(&mut self.state, tech).map_err(Rejection::Research)?;
+        let days = self
+            .state
+            .research
+            .current
+            .as_ref()
+            .map_or(0, |project| project.days_left);
+
+        return Ok(Event::ResearchStarted {
+            tech: tech.to_string(),
+            days,
+            changes,
+        });
+    }
+
+    fn undo(&mut self) -> Result<Event, Rejection> {
+        if !self.undo_enabled() {
+            return Err(Rejection::UndoDisabled);
+        }
+
+        let Some(previous) = self.undo_stack.undo(&self.state) else {
+            return Err(Rejection::NothingToUndo);
+        };
+
+        let changes = Changes::between(&self.state, &previous);
+        self.state = previous;
+
+        return Ok(Event::Undone { changes });
+    }
+
+    fn redo(&mut self) -> Result<Event, Rejection> {
+        if !self.undo_enabled() {
+            return Err(Rejection::UndoDisabled);
+        }
+
+        let Some(next) = self.undo_stack.redo(&self.state) else {
+            return Err(Rejection::NothingToRedo);
+        };
+
+        let changes = Changes::between(&self.state, &next);
+        self.state = next;
+
+        return Ok(Event::Redone { changes });
+    }
+
+    fn pass_days(&mut self, days: i32, stop_on: StopOn) -> Result<Event, Rejection> {
+        if days <= 0 {
+            return Err(Rejection::InvalidAmount(days));
+        }
+
+        let before = self.state.clone();
+        let passed = self.state.pass_days(days, stop_on);
+
+        return Ok(Event::DaysPassed {
+            requested: days,
+            changes: Changes::between(&before, &self.state),
+            passed,
+        });
+    }
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rejection::GameOver => write!(f, "The game is over"),
+            Rejection::InvalidAmount(amount) => write!(f, "{amount} is not a valid amount"),
+            Rejection::UnknownAction { item, action } => {
+                write!(f, "{item} has no action named {action}")
+            }
+            Rejection::Locked(item) => write!(f, "{item} has not been researched yet"),
+            Rejection::Action(error) => write!(f, "{error}"),
+            Rejection::Research(error) => write!(f, "{error}"),
+            Rejection::UndoDisabled => write!(f, "Undo is disabled in hardcore mode"),
+            Rejection::NothingToUndo => write!(f, "Nothing to undo"),
+            Rejection::NothingToRedo => write!(f, "Nothing to redo"),
+        }
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::ActionApplied {
+                item,
+                action,
+                amount,
+                changes,
+            } => write!(f, "{action} {item} x{amount}: {changes}"),
+            Event::CommandRejected { command, reason } => {
+                write!(f, "rejected `{command}`: {reason}")
+            }
+            Event::DaysPassed {
+                requested,
+                changes,
+                passed,
+            } => {
+                write!(f, "passed {} day(s): {changes}", passed.days.len())?;
+                for day in &passed.days {
+                    write!(f, "\n  {}", day.to_string().replace('\n', "\n  "))?;
+                }
+                match &passed.stopped {
+                    Some(reason) if passed.days.len() < *requested as usize => {
+                        write!(f, "\n  stopped early: {reason}")
+                    }
+                    _ => Ok(()),
+                }
+            }
+            Event::Traded {
+                offer,
+                amount,
+                changes,
+            } => write!(f, "traded {offer} x{amount}: {changes}"),
+            Event::BuildCancelled {
+                item,
+                amount,
+                changes,
+            } => write!(f, "cancelled {item} x{amount}: {changes}"),
+            Event::ResearchStarted {
+                tech,
+                days: 0,
+                changes,
+            } => write!(f, "researched {tech}: {changes}"),
+            Event::ResearchStarted {
+                tech,
+                days,
+                changes,
+            } => write!(f, "researching {tech} for {days} day(s): {changes}"),
+            Event::Undone { changes } => write!(f, "undone: {changes}"),
+            Event::Redone { changes } => write!(f, "redone: {changes}"),
+            Event::GameEnded { net_worth } => {
+                write!(f, "game ended with a net worth of {net_worth}")
+            }
+        }
+    }
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Command::Activate {
+                item,
+                action,
+                amount,
+            } => write!(f, "{action} {item} {amount}"),
+            Command::PassDays { days, stop_on } if *stop_on == StopOn::default() => {
+                write!(f, "pass {days}")
+            }
+            Command::PassDays { days, stop_on } => write!(f, "pass {days} until {stop_on}"),
+            Command::Trade { slot, amount } => write!(f, "trade {} {amount}", slot + 1),
+            Command::Cancel { slot } => write!(f, "cancel {}", slot + 1),
+            Command::Research { tech } => write!(f, "research {tech}"),
+            Command::Undo => write!(f, "undo"),
+            Command::Redo => write!(f, "redo"),
+        }
+    }
+}
+
+// parses the commands of a script, one per line:
+// `pass <days> [until event|depleted ...]`,
+// `trade <offer> [amount]`, `cancel <build>`, `research <tech>`,
+// `undo`, `redo` or `<action> <item> [amount]`
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words = s.split_whitespace().collect::<Vec<&str>>();
+
+        let parse_amount = |word: Option<&&str>| match word {
+            Some(word) => word
+                .parse::<i32>()
+                .map_err(|_| format!("`{word}` is not a number")),
+            None => Ok(1),
+        };
+
+        return match words.as_slice() {
+            [] => Err("empty command".to_string()),
+            ["trade", offer, rest @ ..] if rest.len() <= 1 => {
+                let slot = offer
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|offer| offer.checked_sub(1))
+                    .ok_or_else(|| format!("`{offer}` is not an offer number"))?;
+
+                Ok(Command::Trade {
+                    slot,
+                    amount: parse_amount(rest.first())?,
+                })
+            }
+            ["cancel", build] => {
+                let slot = build
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|build| build.checked_sub(1))
+                    .ok_or_else(|| format!("`{build}` is not a build number"))?;
+
+                Ok(Command::Cancel { slot })
+            }
+            ["research", tech @ ..] if !tech.is_empty() => Ok(Command::Research {
+                tech: tech.join(" "),
+            }),
+            ["undo"] => Ok(Command::Undo),
+            ["redo"] => Ok(Command::Redo),
+            ["pass", rest @ ..] if rest.len() <= 1 => Ok(Command::PassDays {
+                days: parse_amount(rest.first())?,
+                stop_on: StopOn::default(),
+            }),
+            ["pass", days, "until", triggers @ ..] if !triggers.is_empty() => {
+                let mut stop_on = StopOn::default();
+                for trigger in triggers {
+                    match *trigger {
+                        "event" => stop_on.event = true,
+                        "depleted" => stop_on.depleted = true,
+                        _ => {
+                            return Err(format!(
+                                "`{trigger}` is not a stop trigger, expected event or depleted"
+                            ))
+                        }
+                    }
+                }
+
+                Ok(Command::PassDays {
+                    days: parse_amount(Some(days))?,
+                    stop_on,
+                })
+            }
+            [action, item, rest @ ..] if rest.len() <= 1 => Ok(Command::Activate {
+                item: item.to_string(),
+                action: action.to_string(),
+                amount: parse_amount(rest.first())?,
+            }),
+            _ => Err(format!("could not understand `{s}`")),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hundred_days::amount::Amount;
+
+    fn play(mut state: GameState, seed: u64, script: &[&str]) -> GameState {
+        state.reseed(seed);
+        let mut engine = Engine::new(state);
+        for line in script {
+            engine.execute(line.parse().unwrap());
+        }
+
+        return engine.state;
+    }
+
+    #[test]
+    fn same_seed_plays_out_the_same() {
+        let script = [
+            "Buy Wood 5",
+            "pass 10",
+            "Construct Sawmill 1",
+            "trade 1",
+            "pass 30",
+            "Sell Stone 10",
+            "pass 70",
+        ];
+
+        let first = play(GameState::generate_default(), 7, &script);
+        let second = play(GameState::generate_default(), 7, &script);
+
+        assert_eq!(first.snapshots, second.snapshots);
+        assert!(first == second);
+    }
+
+    // products sharing a category capacity have to get
+    // their room in the same order in every game
+    #[test]
+    fn production_is_stored_in_the_same_order() {
+        let scenario = r#"{
+            "day": 10,
+            "currency": 0,
+            "categories": [{ "name": "Resource", "capacity": 10 }, { "name": "Building" }],
+            "items": {
+                "Coal": { "name": "Coal", "amount": 0, "category": "Resource",
+                    "actions_active": [], "actions_passive": [] },
+                "Gold": { "name": "Gold", "amount": 0, "category": "Resource",
+                    "actions_active": [], "actions_passive": [] },
+                "Iron": { "name": "Iron", "amount": 0, "category": "Resource",
+                    "actions_active": [], "actions_passive": [] },
+                "Mine": { "name": "Mine", "amount": 1, "category": "Building",
+                    "actions_active": [],
+                    "actions_passive": [
+                        { "Produce": { "item_production": { "Iron": 4, "Coal": 4, "Gold": 4 } } }
+                    ] }
+            }
+        }"#;
+
+        for seed in 0..5 {
+            let state = GameState::generate_from_json("test.json", scenario).unwrap();
+            let state = play(state, seed, &["pass 1"]);
+
+            let amount = |name: &str| state.items[name].amount;
+            assert_eq!(amount("Coal"), Amount::new(4));
+            assert_eq!(amount("Gold"), Amount::new(4));
+            assert_eq!(amount("Iron"), Amount::new(2));
+        }
+    }
+
+    #[test]
+    fn overflowing_actions_are_rejected() {
+        let mut state = GameState::generate_default();
+        state.currency = Amount::MAX;
+        let before = state.items["Wood"].amount;
+
+        let mut engine = Engine::new(state);
+        let events = engine.execute("Sell Wood 1".parse().unwrap());
+
+        assert!(matches!(
+            events.as_slice(),
+            [Event::CommandRejected {
+                reason: Rejection::Action(ActionError::Overflow),
+                ..
+            }]
+        ));
+        assert_eq!(engine.state.currency, Amount::MAX);
+        assert_eq!(engine.state.items["Wood"].amount, before);
+    }
+
+    fn rejection(events: &[Event]) -> Option<&Rejection> {
+        return match events {
+            [Event::CommandRejected { reason, .. }] => Some(reason),
+            _ => None,
+        };
+    }
+
+    #[test]
+    fn undo_and_redo_step_through_actions_and_days() {
+        let mut engine = Engine::new(GameState::generate_default());
+        let start = engine.state.clone();
+
+        engine.execute("Buy Wood 5".parse().unwrap());
+        let bought = engine.state.clone();
+        engine.execute("pass 3".parse().unwrap());
+        let passed = engine.state.clone();
+
+        engine.execute(Command::Undo);
+        assert!(engine.state == bought);
+        engine.execute(Command::Undo);
+        assert!(engine.state == start);
+        let events = engine.execute(Command::Undo);
+        assert_eq!(rejection(&events), Some(&Rejection::NothingToUndo));
+
+        engine.execute(Command::Redo);
+        engine.execute(Command::Redo);
+        assert!(engine.state == passed);
+
+        // anything new makes what was undone unrecoverable
+        engine.execute(Command::Undo);
+        engine.execute("Sell Wood 1".parse().unwrap());
+        let events = engine.execute(Command::Redo);
+        assert_eq!(rejection(&events), Some(&Rejection::NothingToRedo));
+    }
+
+    #[test]
+    fn rejected_commands_are_not_undone() {
+        let mut engine = Engine::new(GameState::generate_default());
+        engine.execute("Buy Wood 5".parse().unwrap());
+        let bought = engine.state.clone();
+
+        engine.execute("Sell Wood 100000".parse().unwrap());
+        engine.execute(Command::Undo);
+
+        assert!(engine.state != bought);
+        assert!(!engine.can_undo());
+    }
+
+    #[test]
+    fn hardcore_games_cannot_undo() {
+        let mut state = GameState::generate_default();
+        state.hardcore = true;
+        let mut engine = Engine::new(state);
+
+        engine.execute("pass 1".parse().unwrap());
+        let passed = engine.state.clone();
+
+        assert!(!engine.can_undo());
+        let events = engine.execute(Command::Undo);
+        assert_eq!(rejection(&events), Some(&Rejection::UndoDisabled));
+        let events = engine.execute(Command::Redo);
+        assert_eq!(rejection(&events), Some(&Rejection::UndoDisabled));
+        assert!(engine.state == passed);
+    }
+}
@@ -1,63 +1,546 @@
-use std::{collections::HashMap, fs};
+use std::{collections::HashMap, fmt, fs, path::Path};
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use super::{
-    action::{active::Active, Action},
-    item::Item,
+    action::{ActionError, Changes},
+    amount::Amount,
+    construction::{self, Build},
+    high_scores::ScenarioId,
+    industries::Synergy,
+    item::{default_categories, Item, ItemCategory},
+    market::{self, MarketItem},
+    night_market::{self, NightMarket},
+    objective::{self, Objective, ObjectiveStatus},
+    production,
+    random_events::{self, RandomEvent},
+    research::{self, Research, Tech},
+    rng::{random_seed, Rng},
+    snapshot::Snapshot,
+    statistics::Statistics,
+    validation::{validate_scenario, ScenarioError, ValidationReport},
+    valuation,
 };
 
-#[derive(Deserialize, Clone, PartialEq)]
+// scenario shipped inside the binary, used when
+// no scenario file is given on the command line
+pub const DEFAULT_SCENARIO: &str = include_str!("../../hundred_days.json");
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
 pub struct GameState {
     pub day: i32,
-    pub currency: i32,
+    pub currency: Amount,
     pub items: HashMap<String, Item>,
+    // panels items are shown in, in order
+    #[serde(default = "default_categories")]
+    pub categories: Vec<ItemCategory>,
+    // everything random in a game comes from `rng`,
+    // so a run can be reproduced from its seed
+    #[serde(default = "random_seed")]
+    pub seed: u64,
+    #[serde(default)]
+    pub rng: Rng,
+    // hardcore scenarios cannot undo
+    #[serde(default)]
+    pub hardcore: bool,
+    // items whose prices move, keyed by item name
+    #[serde(default)]
+    pub market: HashMap<String, MarketItem>,
+    #[serde(default)]
+    pub night_market: Option<NightMarket>,
+    #[serde(default)]
+    pub events: Vec<RandomEvent>,
+    #[serde(default)]
+    pub synergies: Vec<Synergy>,
+    // buildings worked on at once, without a
+    // limit every queued build is worked on
+    #[serde(default)]
+    pub builders: Option<i32>,
+    #[serde(default)]
+    pub build_queue: Vec<Build>,
+    #[serde(default)]
+    pub techs: Vec<Tech>,
+    #[serde(default)]
+    pub research: Research,
+    // one per day played, oldest first
+    #[serde(default)]
+    pub snapshots: Vec<Snapshot>,
+    #[serde(default)]
+    pub statistics: Statistics,
+    // set when the scenario is loaded
+    #[serde(default)]
+    pub scenario: ScenarioId,
+    // shown when picking a scenario
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub author: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub difficulty: Difficulty,
+    #[serde(default)]
+    pub objective: Objective,
+    #[serde(default)]
+    pub objective_status: ObjectiveStatus,
+}
+
+// how hard a scenario says it is, only shown to the player
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Difficulty::Easy => write!(f, "Easy"),
+            Difficulty::Normal => write!(f, "Normal"),
+            Difficulty::Hard => write!(f, "Hard"),
+        }
+    }
+}
+
+// something notable that happened while days passed
+#[derive(Debug, Clone, PartialEq)]
+pub enum DayEvent {
+    RandomEvent {
+        day: i32,
+        name: String,
+        description: String,
+        changes: Changes,
+    },
+    // nothing was produced by the item that day
+    UpkeepUnpaid {
+        day: i32,
+        item: String,
+        reason: ActionError,
+        // one unit of the item was lost
+        decayed: bool,
+    },
+    // upkeep was paid with money the player does not have
+    UpkeepDebt {
+        day: i32,
+        item: String,
+        debt: Amount,
+    },
+    // a conversion ran fewer times than owned
+    // for lack of inputs or storage for outputs
+    ConversionShort {
+        day: i32,
+        item: String,
+        fired: i32,
+        wanted: i32,
+    },
+    // a build in the build queue was finished
+    Constructed {
+        day: i32,
+        item: String,
+        amount: i32,
+    },
+    ResearchFinished {
+        day: i32,
+        tech: String,
+    },
+    // the objective of the scenario was decided
+    Objective {
+        day: i32,
+        objective: Objective,
+        reached: bool,
+    },
+}
+
+// which of the optional reasons end
+// a pass of several days early
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub struct StopOn {
+    // something happened, see `DayEvent`
+    pub event: bool,
+    // an item ran out
+    pub depleted: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StopReason {
+    GameEnded,
+    Event,
+    Depleted(String),
+}
+
+// everything a single day changed
+#[derive(Debug, Clone, PartialEq)]
+pub struct DaySummary {
+    // the day it became
+    pub day: i32,
+    pub changes: Changes,
+    pub events: Vec<DayEvent>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PassedDays {
+    pub days: Vec<DaySummary>,
+    // why fewer days than asked for were passed,
+    // or why the last day was the last one
+    pub stopped: Option<StopReason>,
+}
+
+impl PassedDays {
+    pub fn events(&self) -> impl Iterator<Item = &DayEvent> {
+        return self.days.iter().flat_map(|day| day.events.iter());
+    }
+}
+
+impl DayEvent {
+    pub fn day(&self) -> i32 {
+        return match self {
+            DayEvent::RandomEvent { day, .. }
+            | DayEvent::UpkeepUnpaid { day, .. }
+            | DayEvent::UpkeepDebt { day, .. }
+            | DayEvent::ConversionShort { day, .. }
+            | DayEvent::Constructed { day, .. }
+            | DayEvent::ResearchFinished { day, .. }
+            | DayEvent::Objective { day, .. } => *day,
+        };
+    }
+
+    // what happened without the day or the exact amounts,
+    // so the same event on several days reads the same
+    pub fn summary(&self) -> String {
+        return match self {
+            DayEvent::RandomEvent {
+                name,
+                description,
+                changes,
+                ..
+            } => {
+                let mut summary = name.clone();
+                if !description.is_empty() {
+                    summary += &format!(" - {description}");
+                }
+                if changes != &Changes::default() {
+                    summary += &format!(" ({changes})");
+                }
+                summary
+            }
+            DayEvent::UpkeepUnpaid {
+                item,
+                decayed: false,
+                ..
+            } => format!("{item} upkeep unpaid, production halted"),
+            DayEvent::UpkeepUnpaid {
+                item,
+                decayed: true,
+                ..
+            } => format!("{item} upkeep unpaid, one {item} fell apart"),
+            DayEvent::UpkeepDebt { item, .. } => format!("{item} upkeep paid in debt"),
+            DayEvent::ConversionShort { item, .. } => format!("{item} short of inputs or storage"),
+            DayEvent::Constructed { item, .. } => format!("Built {item}"),
+            DayEvent::ResearchFinished { tech, .. } => format!("Researched {tech}"),
+            DayEvent::Objective {
+                objective,
+                reached: true,
+                ..
+            } => format!("Objective reached: {objective}"),
+            DayEvent::Objective {
+                objective,
+                reached: false,
+                ..
+            } => format!("Objective failed: {objective}"),
+        };
+    }
+}
+
+impl fmt::Display for StopOn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.event, self.depleted) {
+            (false, false) => write!(f, "never"),
+            (true, false) => write!(f, "event"),
+            (false, true) => write!(f, "depleted"),
+            (true, true) => write!(f, "event depleted"),
+        }
+    }
+}
+
+impl fmt::Display for StopReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StopReason::GameEnded => write!(f, "the game ended"),
+            StopReason::Event => write!(f, "something happened"),
+            StopReason::Depleted(item) => write!(f, "ran out of {item}"),
+        }
+    }
+}
+
+impl fmt::Display for DaySummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Day {}: {}", self.day, self.changes)?;
+        for event in &self.events {
+            write!(f, "\n  {event}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for DayEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.summary())?;
+
+        match self {
+            DayEvent::RandomEvent { .. }
+            | DayEvent::ResearchFinished { .. }
+            | DayEvent::Objective { .. } => Ok(()),
+            DayEvent::Constructed { amount, .. } => write!(f, " (+{amount})"),
+            DayEvent::UpkeepUnpaid { reason, .. } => write!(f, " ({reason})"),
+            DayEvent::UpkeepDebt { debt, .. } => write!(f, " ({debt} owed)"),
+            DayEvent::ConversionShort { fired, wanted, .. } => {
+                write!(f, " (converted {fired} of {wanted})")
+            }
+        }
+    }
 }
 
 impl GameState {
-    pub fn generate_from_json() -> Self {
-        let file_path = "hundred_days.json";
-        let contents =
-            fs::read_to_string(file_path).expect("Should have been able to read the file");
+    pub fn generate_from_json(source_name: &str, contents: &str) -> Result<Self, ValidationReport> {
+        let mut game = validate_scenario(source_name, contents)?;
+        let name = Path::new(source_name)
+            .file_name()
+            .map_or(source_name.to_string(), |name| {
+                name.to_string_lossy().to_string()
+            });
+        game.scenario = ScenarioId::new(&name, contents);
+        game.start();
+
+        return Ok(game);
+    }
 
-        let game: GameState = serde_json::from_str(&contents).unwrap();
+    // sets up what a scenario leaves to the game
+    // before the first day is played
+    fn start(&mut self) {
+        self.rng = Rng::new(self.seed);
 
-        return game;
+        // reseeding starts again, so the first
+        // offers are never counted twice
+        if let Some(night_market) = self.night_market.as_mut() {
+            night_market.rotation = 0;
+            night_market.refresh(&mut self.rng);
+        }
     }
 
-    pub fn pass_day(&mut self, amount: i32) {
-        self.day -= amount;
+    // a game with a changed starting day is kept
+    // apart from the scenario in the high scores
+    pub fn override_day(&mut self, day: i32) {
+        self.day = day;
+        self.scenario.mix("day", &day.to_string());
+    }
 
-        self.clone().items.values().for_each(|i| {
-            i.actions_passive.iter().for_each(|p| {
-                p.activate(i.name.clone(), self, amount);
+    // every category of the scenario followed by
+    // the ones only used by items, alphabetically
+    pub fn item_categories(&self) -> Vec<ItemCategory> {
+        let mut undeclared = self
+            .items
+            .values()
+            .map(|item| &item.category)
+            .filter(|name| {
+                !self
+                    .categories
+                    .iter()
+                    .any(|category| category.name == **name)
             })
-        });
-    }
-
-    pub fn net_worth(&self) -> i32 {
-        // all items that can be will be turned into
-        // currency in this copy
-        let mut cash_game = self.clone();
-
-        for (item_name, item) in cash_game.items.clone() {
-            for action in item.actions_active.clone() {
-                match action {
-                    Active::Sell { sell_price: _ } => {
-                        while cash_game.items.get(&item_name).unwrap().amount > 0 {
-                            action.activate(item_name.clone(), &mut cash_game, 1);
-                        }
-                    }
-                    Active::Deconstruct { item_gain: _ } => {
-                        while cash_game.items.get(&item_name).unwrap().amount > 0 {
-                            action.activate(item_name.clone(), &mut cash_game, 1);
-                        }
-                    }
-                    _ => continue,
-                }
+            .collect::<Vec<&String>>();
+        undeclared.sort();
+        undeclared.dedup();
+
+        let mut categories = self.categories.clone();
+        categories.extend(
+            undeclared
+                .into_iter()
+                .map(|name| ItemCategory::new(name, None)),
+        );
+
+        return categories;
+    }
+
+    // restarts a game that has not been played
+    // yet with a different random sequence
+    pub fn reseed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.start();
+    }
+
+    pub fn generate_from_file(file_path: &Path) -> Result<Self, ScenarioError> {
+        let contents = fs::read_to_string(file_path).map_err(|error| ScenarioError::Io {
+            path: file_path.to_path_buf(),
+            error,
+        })?;
+
+        return Self::generate_from_json(&file_path.display().to_string(), &contents)
+            .map_err(ScenarioError::Invalid);
+    }
+
+    pub fn generate_default() -> Self {
+        return Self::generate_from_json("hundred_days.json", DEFAULT_SCENARIO)
+            .expect("Built in scenario should always be valid");
+    }
+
+    pub fn pass_day(&mut self) -> DaySummary {
+        // taken here rather than when the game starts
+        // so overrides of the starting day are included
+        if self.snapshots.is_empty() {
+            self.snapshots.push(Snapshot::take(self));
+        }
+
+        let before = self.clone();
+        let mut events = Vec::new();
+
+        // checked before the day passes as well, for
+        // targets reached by trading on the last day
+        events.extend(objective::update(self));
+
+        self.day -= 1;
+        market::pass_day(self);
+        night_market::pass_day(self);
+        events.append(&mut random_events::pass_day(self));
+        events.append(&mut production::pass_day(self));
+        events.append(&mut construction::pass_day(self));
+        events.extend(research::pass_day(self));
+        events.extend(objective::update(self));
+        self.snapshots.push(Snapshot::take(self));
+
+        return DaySummary {
+            day: self.day,
+            changes: Changes::between(&before, self),
+            events,
+        };
+    }
+
+    // passes up to `amount` days one at a time, stopping
+    // after the day the game ends or one of `stop_on` happens
+    pub fn pass_days(&mut self, amount: i32, stop_on: StopOn) -> PassedDays {
+        let mut passed = PassedDays::default();
+
+        for _ in 0..amount {
+            let summary = self.pass_day();
+            passed.stopped = self.stop_reason(&summary, stop_on);
+            passed.days.push(summary);
+
+            if passed.stopped.is_some() {
+                break;
             }
         }
 
-        return cash_game.currency;
+        return passed;
+    }
+
+    fn stop_reason(&self, summary: &DaySummary, stop_on: StopOn) -> Option<StopReason> {
+        if self.day < 0 {
+            return Some(StopReason::GameEnded);
+        }
+
+        if stop_on.event && !summary.events.is_empty() {
+            return Some(StopReason::Event);
+        }
+
+        if stop_on.depleted {
+            let depleted = summary.changes.items.iter().find(|(name, delta)| {
+                delta.is_negative()
+                    && self
+                        .items
+                        .get(*name)
+                        .is_some_and(|item| !item.amount.is_positive())
+            });
+            if let Some((name, _)) = depleted {
+                return Some(StopReason::Depleted(name.clone()));
+            }
+        }
+
+        return None;
+    }
+
+    pub fn net_worth(&self) -> Amount {
+        return valuation::valuate(self).total();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // an Oven burning a Wood a day from a pile of three
+    fn game() -> GameState {
+        let scenario = r#"{
+            "day": 10,
+            "currency": 0,
+            "items": {
+                "Wood": { "name": "Wood", "amount": 3, "category": "Resource",
+                    "actions_active": [], "actions_passive": [] },
+                "Oven": { "name": "Oven", "amount": 1, "category": "Building",
+                    "actions_active": [],
+                    "actions_passive": [{ "Reduce": { "item_reduction": { "Wood": 1 } } }] }
+            }
+        }"#;
+
+        return GameState::generate_from_json("test.json", scenario).unwrap();
+    }
+
+    const NEVER: StopOn = StopOn {
+        event: false,
+        depleted: false,
+    };
+
+    #[test]
+    fn days_are_passed_one_at_a_time() {
+        let mut game = game();
+        let passed = game.pass_days(2, NEVER);
+
+        assert_eq!(passed.stopped, None);
+        let days = passed.days.iter().map(|day| day.day).collect::<Vec<i32>>();
+        assert_eq!(days, [9, 8]);
+        assert_eq!(game.items["Wood"].amount, Amount::new(1));
+    }
+
+    #[test]
+    fn passing_days_stops_when_an_item_runs_out() {
+        let mut game = game();
+        let stop_on = StopOn {
+            depleted: true,
+            ..NEVER
+        };
+        let passed = game.pass_days(10, stop_on);
+
+        assert_eq!(passed.days.len(), 3);
+        assert_eq!(
+            passed.stopped,
+            Some(StopReason::Depleted("Wood".to_string()))
+        );
+    }
+
+    #[test]
+    fn passing_days_stops_on_events() {
+        let mut game = game();
+        game.pass_days(3, NEVER);
+
+        // the Oven cannot be kept going without Wood
+        let stop_on = StopOn {
+            event: true,
+            ..NEVER
+        };
+        let passed = game.pass_days(5, stop_on);
+
+        assert_eq!(passed.days.len(), 1);
+        assert_eq!(passed.stopped, Some(StopReason::Event));
+        assert!(!passed.days[0].events.is_empty());
+    }
+
+    #[test]
+    fn passing_days_always_stops_when_the_game_ends() {
+        let mut game = game();
+        let passed = game.pass_days(50, NEVER);
+
+        assert_eq!(passed.stopped, Some(StopReason::GameEnded));
+        assert_eq!(passed.days.len(), 11);
+        assert!(game.day < 0);
     }
 }
@@ -0,0 +1,231 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::amount::Amount;
+
+// scores kept in every table
+pub const HIGH_SCORES_KEPT: usize = 10;
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+// continues an FNV-1a hash with more bytes
+pub fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    return bytes.iter().fold(hash, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(FNV_PRIME)
+    });
+}
+
+// the scenario a game was started from. The hash covers the
+// scenario file and anything overridden when starting, so
+// scores of changed scenarios are never compared
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ScenarioId {
+    pub name: String,
+    pub hash: u64,
+}
+
+impl ScenarioId {
+    pub fn new(name: &str, contents: &str) -> Self {
+        ScenarioId {
+            name: name.to_string(),
+            hash: fnv1a(FNV_OFFSET, contents.as_bytes()),
+        }
+    }
+
+    // marks the scenario as changed by an override
+    pub fn mix(&mut self, override_name: &str, value: &str) {
+        self.hash = fnv1a(self.hash, format!("\n{override_name}={value}").as_bytes());
+    }
+
+    // scores are kept per scenario and seed
+    pub fn key(&self, seed: u64) -> String {
+        return format!("{:016x}-{seed}", self.hash);
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HighScore {
+    pub name: String,
+    pub net_worth: Amount,
+    // seed the game was played with, the same
+    // as the seed of the table it is kept in
+    #[serde(default)]
+    pub seed: u64,
+    // as year-month-day
+    pub date: String,
+}
+
+// best scores of one scenario and seed, highest first
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct HighScoreTable {
+    pub scenario: String,
+    #[serde(default)]
+    pub seed: u64,
+    pub scores: Vec<HighScore>,
+}
+
+impl HighScoreTable {
+    pub fn best(&self) -> Option<Amount> {
+        return self.scores.first().map(|score| score.net_worth);
+    }
+
+    // returns the place the score took, counting from 0,
+    // or nothing if it was not good enough to be kept
+    pub fn insert(&mut self, score: HighScore) -> Option<usize> {
+        // ties go to the older score
+        let place = self
+            .scores
+            .iter()
+            .position(|kept| kept.net_worth < score.net_worth)
+            .unwrap_or(self.scores.len());
+
+        if place >= HIGH_SCORES_KEPT {
+            return None;
+        }
+
+        self.scores.insert(place, score);
+        self.scores.truncate(HIGH_SCORES_KEPT);
+
+        return Some(place);
+    }
+
+    pub fn remove(&mut self, score: &HighScore) {
+        if let Some(index) = self.scores.iter().position(|kept| kept == score) {
+            self.scores.remove(index);
+        }
+    }
+}
+
+// every table, keyed by `ScenarioId::key`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct HighScores {
+    pub tables: BTreeMap<String, HighScoreTable>,
+}
+
+impl HighScores {
+    // for a while tables were kept per scenario only, keyed
+    // without a seed. Their scores are moved to the table of
+    // the seed they were played with
+    pub fn migrate(&mut self) {
+        for (key, mut table) in std::mem::take(&mut self.tables) {
+            let hash = key.split_once('-').map_or(key.as_str(), |(hash, _)| hash);
+            let keyed_by_seed = hash != key;
+
+            for mut score in std::mem::take(&mut table.scores) {
+                // scores saved before they had a seed
+                // take the seed of their table
+                if keyed_by_seed {
+                    score.seed = table.seed;
+                }
+
+                self.tables
+                    .entry(format!("{hash}-{}", score.seed))
+                    .or_insert_with(|| HighScoreTable {
+                        scenario: table.scenario.clone(),
+                        seed: score.seed,
+                        scores: Vec::new(),
+                    })
+                    .insert(score);
+            }
+        }
+    }
+}
+
+// year-month-day of a unix timestamp, from Howard
+// Hinnant's days-to-civil algorithm
+pub fn date_from_unix(seconds: u64) -> String {
+    let days = (seconds / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    return format!("{year:04}-{month:02}-{day:02}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(name: &str, net_worth: i64, seed: u64) -> HighScore {
+        return HighScore {
+            name: name.to_string(),
+            net_worth: Amount::new(net_worth),
+            seed,
+            date: "2026-10-17".to_string(),
+        };
+    }
+
+    #[test]
+    fn keys_tell_scenarios_overrides_and_seeds_apart() {
+        let scenario = ScenarioId::new("a.json", "{}");
+        let mut overridden = scenario.clone();
+        overridden.mix("day", "50");
+
+        assert_eq!(scenario.key(3), format!("{:016x}-3", scenario.hash));
+        assert_ne!(scenario.key(3), scenario.key(4));
+        assert_ne!(scenario.key(3), overridden.key(3));
+        assert_ne!(scenario.key(3), ScenarioId::new("a.json", "{ }").key(3));
+    }
+
+    #[test]
+    fn tables_keep_the_best_scores_with_ties_to_the_older() {
+        let mut table = HighScoreTable::default();
+        for net_worth in 0..HIGH_SCORES_KEPT as i64 {
+            table.insert(score("old", net_worth, 0));
+        }
+
+        assert_eq!(table.insert(score("new", 5, 0)), Some(5));
+        assert_eq!(table.scores[4].name, "old");
+        assert_eq!(table.insert(score("low", 0, 0)), None);
+        assert_eq!(table.scores.len(), HIGH_SCORES_KEPT);
+        assert_eq!(table.best(), Some(Amount::new(9)));
+    }
+
+    #[test]
+    fn tables_without_a_seed_are_split_by_seed() {
+        let mut high_scores = HighScores::default();
+        high_scores.tables.insert(
+            "00000000000000ab".to_string(),
+            HighScoreTable {
+                scenario: "a.json".to_string(),
+                seed: 0,
+                scores: vec![score("ann", 30, 1), score("bob", 20, 2)],
+            },
+        );
+        high_scores.tables.insert(
+            "00000000000000ab-1".to_string(),
+            HighScoreTable {
+                scenario: "a.json".to_string(),
+                seed: 1,
+                scores: vec![score("cy", 25, 0)],
+            },
+        );
+
+        high_scores.migrate();
+
+        let names = |key: &str| {
+            let table = &high_scores.tables[key];
+            assert!(table.scores.iter().all(|score| score.seed == table.seed));
+            return table
+                .scores
+                .iter()
+                .map(|score| score.name.as_str())
+                .collect::<Vec<&str>>();
+        };
+        assert_eq!(high_scores.tables.len(), 2);
+        assert_eq!(names("00000000000000ab-1"), ["ann", "cy"]);
+        assert_eq!(names("00000000000000ab-2"), ["bob"]);
+    }
+}
@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    amount::{Amount, AmountOverflow},
+    game::GameState,
+    item::Item,
+};
+
+// a bonus for items tagged with `industry` while everything
+// in `requires` is owned, defined under `synergies`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Synergy {
+    pub name: String,
+    pub industry: String,
+    #[serde(default)]
+    pub requires: HashMap<String, Amount>,
+    // share added to everything produced, 0.2 for +20%
+    pub production_bonus: f64,
+}
+
+impl Synergy {
+    pub fn applies_to(&self, item: &Item) -> bool {
+        return item.industries.contains(&self.industry);
+    }
+
+    pub fn is_active(&self, game: &GameState) -> bool {
+        return self.requires.iter().all(|(name, required)| {
+            game.items
+                .get(name)
+                .is_some_and(|item| item.amount >= *required)
+        });
+    }
+}
+
+// every synergy for the industries of an item,
+// in the order the scenario defines them
+pub fn synergies<'a>(game: &'a GameState, item_name: &str) -> Vec<&'a Synergy> {
+    let Some(item) = game.items.get(item_name) else {
+        return Vec::new();
+    };
+
+    return game
+        .synergies
+        .iter()
+        .filter(|synergy| synergy.applies_to(item))
+        .collect();
+}
+
+// bonuses of all active synergies of an item added up
+pub fn production_bonus(game: &GameState, item_name: &str) -> f64 {
+    return synergies(game, item_name)
+        .into_iter()
+        .filter(|synergy| synergy.is_active(game))
+        .map(|synergy| synergy.production_bonus)
+        .sum();
+}
+
+// `produced` with the bonus added, rounded
+// down so bonuses never give partial items
+pub fn with_bonus(produced: Amount, bonus: f64) -> Result<Amount, AmountOverflow> {
+    return produced.checked_add(Amount::from_f64(produced.as_f64() * bonus)?);
+}
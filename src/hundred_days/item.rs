@@ -1,19 +1,68 @@
-use serde::Deserialize;
+use std::collections::HashMap;
 
-use super::action::{active::Active, passive::Passive, Action};
+use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Debug, Clone, PartialEq)]
-pub enum ItemCategory {
-    Resource,
-    Building,
+use super::{
+    action::{active::Active, passive::Passive, Action},
+    amount::Amount,
+};
+
+// a group of items shown in a panel of its own,
+// defined under `categories` in the scenario
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ItemCategory {
+    pub name: String,
+    // shown above the panel instead of the name
+    #[serde(default)]
+    pub title: Option<String>,
+    // how much all items of the category can keep together
+    #[serde(default)]
+    pub capacity: Option<Amount>,
+}
+
+impl ItemCategory {
+    pub fn new(name: &str, title: Option<&str>) -> Self {
+        ItemCategory {
+            name: name.to_string(),
+            title: title.map(str::to_string),
+            capacity: None,
+        }
+    }
+
+    pub fn title(&self) -> &str {
+        return self.title.as_deref().unwrap_or(&self.name);
+    }
+}
+
+// scenarios from before categories could be
+// defined only have resources and buildings
+pub fn default_categories() -> Vec<ItemCategory> {
+    return vec![
+        ItemCategory::new("Resource", Some("Resources")),
+        ItemCategory::new("Building", Some("Buildings")),
+    ];
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Item {
     pub name: String,
-    pub amount: i32,
-    pub category: ItemCategory,
+    pub amount: Amount,
+    // name of an `ItemCategory`
+    pub category: String,
+    // tags that `Synergy` bonuses apply to
+    #[serde(default)]
     pub industries: Vec<String>,
+    // order in which passives run each day,
+    // highest first
+    #[serde(default)]
+    pub priority: i32,
+    // how much of the item can be kept, unlimited without one
+    #[serde(default)]
+    pub capacity: Option<Amount>,
+    // capacity every owned unit adds, keyed by
+    // the name of an item or a category
+    #[serde(default)]
+    pub storage: HashMap<String, Amount>,
     pub actions_active: Vec<Active>,
     pub actions_passive: Vec<Passive>,
 }
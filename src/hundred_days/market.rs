@@ -0,0 +1,289 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+    amount::{Amount, AmountOverflow},
+    game::GameState,
+};
+
+fn default_multiplier() -> f64 {
+    1.0
+}
+
+pub fn default_floor() -> f64 {
+    0.25
+}
+
+pub fn default_ceiling() -> f64 {
+    4.0
+}
+
+// smallest slippage other than 0. Trades move the price
+// one unit at a time, so a smaller one would take too
+// many steps to get anywhere
+pub const MIN_SLIPPAGE: f64 = 0.001;
+
+fn default_reversion() -> f64 {
+    0.1
+}
+
+// how the prices of an item move, defined per item
+// under `market` in the scenario. Prices of items
+// without an entry never change
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MarketItem {
+    // largest random change of the price per day,
+    // as a fraction of the base price
+    #[serde(default)]
+    pub volatility: f64,
+    // change of the price for every unit the
+    // player buys (up) or sells (down)
+    #[serde(default)]
+    pub slippage: f64,
+    // share of the distance to the base price
+    // that is recovered every day
+    #[serde(default = "default_reversion")]
+    pub reversion: f64,
+    // lowest and highest price as
+    // multiples of the base price
+    #[serde(default = "default_floor")]
+    pub floor: f64,
+    #[serde(default = "default_ceiling")]
+    pub ceiling: f64,
+
+    // current price as a multiple of the base
+    // price, changed while the game is played
+    #[serde(default = "default_multiplier")]
+    pub multiplier: f64,
+}
+
+impl MarketItem {
+    // validation keeps the floor below the ceiling, this
+    // still never panics for a save edited by hand
+    pub fn clamp(&self, multiplier: f64) -> f64 {
+        return multiplier.max(self.floor).min(self.ceiling);
+    }
+
+    // total price of trading `amount` units one after
+    // another, each unit moving the price by `slippage`.
+    // Buyers pay the price before their unit moves it up
+    // and sellers get the price after their unit moved it
+    // down, so selling what was just bought never earns
+    // more than it cost
+    fn quote(
+        &self,
+        base_price: Amount,
+        amount: i32,
+        buying: bool,
+    ) -> Result<(Amount, f64), AmountOverflow> {
+        let step = if buying {
+            1.0 + self.slippage
+        } else {
+            1.0 - self.slippage
+        };
+
+        let mut multiplier = self.multiplier;
+        let mut total = Amount::ZERO;
+        for traded in 0..amount {
+            let next = self.clamp(multiplier * step);
+            let price = if buying {
+                unit_price(base_price, multiplier)?
+            } else {
+                unit_price(base_price, next)?
+            };
+            total = total.checked_add(price)?;
+
+            // once the price stops moving every
+            // remaining unit costs the same
+            if next == multiplier {
+                total = total.checked_add(price.checked_mul(amount - traded - 1)?)?;
+                break;
+            }
+            multiplier = next;
+        }
+
+        return Ok((total, multiplier));
+    }
+
+    fn drift(&mut self, noise: f64) {
+        let reverted = self.multiplier + (1.0 - self.multiplier) * self.reversion;
+
+        self.multiplier = self.clamp(reverted + noise * self.volatility);
+    }
+}
+
+fn unit_price(base_price: Amount, multiplier: f64) -> Result<Amount, AmountOverflow> {
+    let price = Amount::from_f64((base_price.as_f64() * multiplier).round())?;
+    return Ok(price.max(Amount::new(1)));
+}
+
+pub fn current_price(
+    game: &GameState,
+    item_name: &str,
+    base_price: Amount,
+) -> Result<Amount, AmountOverflow> {
+    return match game.market.get(item_name) {
+        Some(market_item) => unit_price(base_price, market_item.multiplier),
+        None => Ok(base_price),
+    };
+}
+
+// what buying or selling `amount` of an item costs or
+// earns right now, without changing the market
+pub fn quote(
+    game: &GameState,
+    item_name: &str,
+    base_price: Amount,
+    amount: i32,
+    buying: bool,
+) -> Result<Amount, AmountOverflow> {
+    return match game.market.get(item_name) {
+        Some(market_item) => Ok(market_item.quote(base_price, amount, buying)?.0),
+        None => base_price.checked_mul(amount),
+    };
+}
+
+// most units of an item that `budget` can buy
+pub fn affordable(game: &GameState, item_name: &str, base_price: Amount, budget: Amount) -> i32 {
+    let Some(market_item) = game.market.get(item_name) else {
+        return budget.times(base_price);
+    };
+
+    let step = 1.0 + market_item.slippage;
+    let mut multiplier = market_item.multiplier;
+    let mut remaining = budget;
+    let mut amount = 0;
+
+    while amount < i32::MAX {
+        let Ok(price) = unit_price(base_price, multiplier) else {
+            break;
+        };
+        if price > remaining {
+            break;
+        }
+
+        let next = market_item.clamp(multiplier * step);
+        // once the price stops moving the rest
+        // of the budget buys at the same price
+        if next == multiplier {
+            return amount.saturating_add(remaining.times(price));
+        }
+
+        remaining = remaining.saturating_sub(price);
+        amount += 1;
+        multiplier = next;
+    }
+
+    return amount;
+}
+
+// moves the price after a trade has been made
+pub fn record_trade(
+    game: &mut GameState,
+    item_name: &str,
+    base_price: Amount,
+    amount: i32,
+    buying: bool,
+) {
+    let Some(market_item) = game.market.get_mut(item_name) else {
+        return;
+    };
+
+    // trades are quoted before they are made,
+    // so the quote cannot overflow here
+    if let Ok((_, multiplier)) = market_item.quote(base_price, amount, buying) {
+        market_item.multiplier = multiplier;
+    }
+}
+
+// prices return towards their base price and move
+// randomly by up to their volatility every day
+pub fn pass_day(game: &mut GameState) {
+    // sorted so the random numbers are drawn
+    // in the same order on every run
+    let mut item_names = game.market.keys().cloned().collect::<Vec<String>>();
+    item_names.sort();
+
+    for item_name in item_names {
+        let noise = game.rng.next_signed();
+        game.market.get_mut(&item_name).unwrap().drift(noise);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hundred_days::action::active::Active;
+
+    // base buy and sell price of an item of the default scenario
+    fn prices(game: &GameState, item_name: &str) -> (Amount, Amount) {
+        let mut buy_price = Amount::ZERO;
+        let mut sell_price = Amount::ZERO;
+        for action in &game.items[item_name].actions_active {
+            match action {
+                Active::Buy { buy_price: price } => buy_price = *price,
+                Active::Sell { sell_price: price } => sell_price = *price,
+                _ => {}
+            }
+        }
+
+        return (buy_price, sell_price);
+    }
+
+    #[test]
+    fn selling_what_was_just_bought_never_earns_more() {
+        let game = GameState::generate_default();
+        assert!(!game.market.is_empty());
+
+        for item_name in game.market.keys() {
+            let (buy_price, sell_price) = prices(&game, item_name);
+
+            for amount in 1..=400 {
+                let mut game = game.clone();
+                let cost = quote(&game, item_name, buy_price, amount, true).unwrap();
+                record_trade(&mut game, item_name, buy_price, amount, true);
+                let earnings = quote(&game, item_name, sell_price, amount, false).unwrap();
+
+                assert!(
+                    earnings <= cost,
+                    "{amount} {item_name} cost {cost} and sold for {earnings}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn slippage_moves_the_price_per_unit() {
+        let mut game = GameState::generate_default();
+        let base_price = Amount::new(100);
+        game.market.get_mut("Gems").unwrap().multiplier = 1.0;
+
+        // 100 + 101 + 102.01, each rounded
+        let cost = quote(&game, "Gems", base_price, 3, true).unwrap();
+        assert_eq!(cost, Amount::new(303));
+
+        record_trade(&mut game, "Gems", base_price, 3, true);
+        let multiplier = game.market["Gems"].multiplier;
+        assert!((multiplier - 1.01_f64.powi(3)).abs() < 1e-9);
+
+        // selling pays the price after each unit moved it down
+        let earnings = quote(&game, "Gems", base_price, 1, false).unwrap();
+        assert_eq!(earnings, Amount::new(102));
+    }
+
+    #[test]
+    fn prices_stay_between_floor_and_ceiling() {
+        let mut game = GameState::generate_default();
+        let base_price = Amount::new(4);
+
+        record_trade(&mut game, "Gems", base_price, 10_000, true);
+        assert_eq!(game.market["Gems"].multiplier, 3.0);
+        assert_eq!(
+            quote(&game, "Gems", base_price, 2, true).unwrap(),
+            Amount::new(24)
+        );
+
+        record_trade(&mut game, "Gems", base_price, 10_000, false);
+        assert_eq!(game.market["Gems"].multiplier, 0.25);
+        assert_eq!(affordable(&game, "Gems", base_price, Amount::new(10)), 10);
+    }
+}
@@ -0,0 +1,252 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    action::{check_materials, item_amount, ActionError, Changes},
+    amount::Amount,
+    game::GameState,
+    rng::Rng,
+    storage,
+};
+
+fn default_refresh_days() -> i32 {
+    7
+}
+
+fn default_offers_shown() -> usize {
+    3
+}
+
+fn default_stock() -> i32 {
+    1
+}
+
+// a deal only available while it is on the night market,
+// paying `price` and giving up `give` to get `receive`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Offer {
+    pub name: String,
+    #[serde(default)]
+    pub price: Amount,
+    #[serde(default)]
+    pub give: HashMap<String, Amount>,
+    pub receive: HashMap<String, Amount>,
+    // times the offer can be taken each time it shows up
+    #[serde(default = "default_stock")]
+    pub stock: i32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StockedOffer {
+    // index into `NightMarket::offers`
+    pub offer: usize,
+    pub remaining: i32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NightMarket {
+    #[serde(default = "default_refresh_days")]
+    pub refresh_days: i32,
+    #[serde(default = "default_offers_shown")]
+    pub offers_shown: usize,
+    pub offers: Vec<Offer>,
+
+    // offers currently for sale, rotated every
+    // `refresh_days` days while the game is played
+    #[serde(default)]
+    pub stocked: Vec<StockedOffer>,
+    #[serde(default)]
+    pub rotation: usize,
+}
+
+impl NightMarket {
+    // puts a random selection of
+    // different offers up for sale
+    pub fn refresh(&mut self, rng: &mut Rng) {
+        let mut candidates = (0..self.offers.len()).collect::<Vec<usize>>();
+        let shown = self.offers_shown.min(candidates.len());
+
+        self.stocked = (0..shown)
+            .map(|_| {
+                let offer = candidates.remove(rng.below(candidates.len()));
+                StockedOffer {
+                    offer,
+                    remaining: self.offers[offer].stock,
+                }
+            })
+            .collect();
+        self.rotation += 1;
+    }
+
+    pub fn days_until_refresh(&self, day: i32) -> i32 {
+        let days = day.rem_euclid(self.refresh_days);
+
+        return if days == 0 { self.refresh_days } else { days };
+    }
+
+    pub fn stocked_offer(&self, slot: usize) -> Option<(&Offer, i32)> {
+        let stocked = self.stocked.get(slot)?;
+
+        return Some((&self.offers[stocked.offer], stocked.remaining));
+    }
+}
+
+impl Offer {
+    pub fn description(&self) -> String {
+        let list = |amounts: &HashMap<String, Amount>| {
+            let mut amounts = amounts
+                .iter()
+                .map(|(name, amount)| format!("{name}: {amount}\n"))
+                .collect::<Vec<String>>();
+            amounts.sort();
+            amounts.concat()
+        };
+
+        let mut description = String::new();
+        if self.price.is_positive() {
+            description += &format!("Price: {}\n", self.price);
+        }
+        if !self.give.is_empty() {
+            description += &format!("Give:\n{}", list(&self.give));
+        }
+        description += &format!("Receive:\n{}", list(&self.receive));
+
+        return description;
+    }
+}
+
+// night market offers are rotated on days
+// that are a multiple of `refresh_days`
+pub fn pass_day(game: &mut GameState) {
+    let Some(night_market) = game.night_market.as_mut() else {
+        return;
+    };
+
+    if game.day.rem_euclid(night_market.refresh_days) == 0 {
+        night_market.refresh(&mut game.rng);
+    }
+}
+
+pub fn trade(game: &mut GameState, slot: usize, amount: i32) -> Result<Changes, ActionError> {
+    let Some((offer, remaining)) = game
+        .night_market
+        .as_ref()
+        .and_then(|night_market| night_market.stocked_offer(slot))
+        .map(|(offer, remaining)| (offer.clone(), remaining))
+    else {
+        return Err(ActionError::UnknownOffer(slot));
+    };
+
+    if amount > remaining {
+        return Err(ActionError::OutOfStock {
+            offer: offer.name,
+            requested: amount,
+            remaining,
+        });
+    }
+
+    let cost = offer.price.checked_mul(amount)?;
+    if cost > game.currency {
+        return Err(ActionError::InsufficientFunds {
+            needed: cost,
+            available: game.currency,
+        });
+    }
+    check_materials(game, &offer.give, amount)?;
+    for name in offer.receive.keys() {
+        item_amount(game, name)?;
+    }
+
+    let mut changes = Changes::default();
+    changes.add_currency(cost.checked_neg()?)?;
+    for (name, given) in &offer.give {
+        changes.add_item(name, given.checked_mul(amount)?.checked_neg()?)?;
+    }
+    for (name, received) in &offer.receive {
+        changes.add_item(name, received.checked_mul(amount)?)?;
+    }
+    storage::check(game, &changes)?;
+    changes.apply(game)?;
+
+    let night_market = game.night_market.as_mut().unwrap();
+    night_market.stocked[slot].remaining -= amount;
+
+    return Ok(changes);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rotation(game: &GameState) -> usize {
+        return game.night_market.as_ref().unwrap().rotation;
+    }
+
+    #[test]
+    fn reseeding_counts_the_first_offers_once() {
+        let mut game = GameState::generate_default();
+        assert_eq!(rotation(&game), 1);
+
+        game.reseed(11);
+        assert_eq!(rotation(&game), 1);
+    }
+
+    #[test]
+    fn offers_rotate_on_multiples_of_refresh_days() {
+        let mut game = GameState::generate_default();
+        let shown = game.night_market.as_ref().unwrap().offers_shown;
+
+        for day in [13, 15, 20] {
+            game.day = day;
+            pass_day(&mut game);
+        }
+        assert_eq!(rotation(&game), 1);
+
+        game.day = 14;
+        pass_day(&mut game);
+        let night_market = game.night_market.as_ref().unwrap();
+        assert_eq!(night_market.rotation, 2);
+        assert_eq!(night_market.stocked.len(), shown);
+        assert_ne!(night_market.stocked[0].offer, night_market.stocked[1].offer);
+        for stocked in &night_market.stocked {
+            assert_eq!(stocked.remaining, night_market.offers[stocked.offer].stock);
+        }
+    }
+
+    #[test]
+    fn offers_can_be_taken_until_they_run_out() {
+        let mut game = GameState::generate_default();
+        let night_market = game.night_market.as_mut().unwrap();
+        let gem_broker = night_market
+            .offers
+            .iter()
+            .position(|offer| offer.name == "Gem Broker")
+            .unwrap();
+        night_market.stocked = vec![StockedOffer {
+            offer: gem_broker,
+            remaining: 2,
+        }];
+        let currency = game.currency;
+        let gems = game.items["Gems"].amount;
+
+        trade(&mut game, 0, 2).unwrap();
+        assert_eq!(
+            game.currency,
+            currency.checked_sub(Amount::new(50)).unwrap()
+        );
+        assert_eq!(
+            game.items["Gems"].amount,
+            gems.checked_add(Amount::new(16)).unwrap()
+        );
+
+        assert!(matches!(
+            trade(&mut game, 0, 1),
+            Err(ActionError::OutOfStock { remaining: 0, .. })
+        ));
+        assert!(matches!(
+            trade(&mut game, 1, 1),
+            Err(ActionError::UnknownOffer(1))
+        ));
+    }
+}
@@ -0,0 +1,131 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    amount::Amount,
+    game::{DayEvent, GameState},
+};
+
+// what the scenario asks of the player, set under
+// `objective` and checked every day by `update`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub enum Objective {
+    // no target, the score is the net worth
+    #[default]
+    MaximizeNetWorth,
+    // reached as soon as the net worth is `target` with
+    // `by_day` days left, failed once fewer days are left
+    TargetNetWorth {
+        target: Amount,
+        #[serde(default)]
+        by_day: i32,
+    },
+    // decided when the game ends, by owning at least
+    // `target` of `item`, never decided without one
+    MaximizeItem {
+        item: String,
+        #[serde(default)]
+        target: Amount,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum ObjectiveStatus {
+    #[default]
+    Pending,
+    Reached {
+        day: i32,
+    },
+    Failed {
+        day: i32,
+    },
+}
+
+impl Objective {
+    // objectives without a target are never won or lost
+    pub fn has_target(&self) -> bool {
+        return match self {
+            Objective::MaximizeNetWorth => false,
+            Objective::TargetNetWorth { .. } => true,
+            Objective::MaximizeItem { target, .. } => target.is_positive(),
+        };
+    }
+}
+
+// decides the objective once it can be and returns the
+// event for it, the status never changes after that
+pub fn update(game: &mut GameState) -> Option<DayEvent> {
+    if game.objective_status != ObjectiveStatus::Pending || !game.objective.has_target() {
+        return None;
+    }
+
+    // the last day played when the game is over
+    let day = game.day.max(0);
+    let over = game.day < 0;
+
+    let reached = match &game.objective {
+        Objective::MaximizeNetWorth => return None,
+        Objective::TargetNetWorth { target, by_day } => {
+            if game.day >= *by_day && game.net_worth() >= *target {
+                true
+            } else if game.day < *by_day {
+                false
+            } else {
+                return None;
+            }
+        }
+        Objective::MaximizeItem { item, target } if over => {
+            game.items
+                .get(item)
+                .map_or(Amount::ZERO, |item| item.amount)
+                >= *target
+        }
+        Objective::MaximizeItem { .. } => return None,
+    };
+
+    game.objective_status = if reached {
+        ObjectiveStatus::Reached { day }
+    } else {
+        ObjectiveStatus::Failed { day }
+    };
+
+    return Some(DayEvent::Objective {
+        day,
+        objective: game.objective.clone(),
+        reached,
+    });
+}
+
+impl fmt::Display for Objective {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Objective::MaximizeNetWorth => write!(f, "make as much as you can"),
+            Objective::TargetNetWorth { target, by_day: 0 } => {
+                write!(f, "reach a net worth of {target}")
+            }
+            Objective::TargetNetWorth { target, by_day } => {
+                write!(f, "reach a net worth of {target} with {by_day} days left")
+            }
+            Objective::MaximizeItem {
+                item,
+                target: Amount::ZERO,
+            } => {
+                write!(f, "own as much {item} as you can")
+            }
+            Objective::MaximizeItem { item, target } => {
+                write!(f, "own at least {target} {item} when the game ends")
+            }
+        }
+    }
+}
+
+impl fmt::Display for ObjectiveStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObjectiveStatus::Pending => write!(f, "not decided yet"),
+            ObjectiveStatus::Reached { day } => write!(f, "won on day {day}"),
+            ObjectiveStatus::Failed { day } => write!(f, "lost on day {day}"),
+        }
+    }
+}
@@ -0,0 +1,228 @@
+use std::collections::HashSet;
+
+use super::{
+    action::{passive::Passive, passive::Shortfall, Action},
+    amount::Amount,
+    game::{DayEvent, GameState},
+    item::Item,
+};
+
+// every owned unit of an item runs its passives once per day,
+// in three rounds over all items: first every upkeep is paid,
+// then items whose upkeep was paid produce, and last they
+// convert. Conversions use what was produced the same day,
+// including the outputs of conversions earlier in the order
+pub fn pass_day(game: &mut GameState) -> Vec<DayEvent> {
+    let mut day_events = Vec::new();
+    let item_names = processing_order(game);
+
+    let mut halted = HashSet::new();
+    for item_name in &item_names {
+        if pay_upkeep(game, item_name, &mut day_events) {
+            halted.insert(item_name.clone());
+        }
+    }
+
+    for item_name in item_names.iter().filter(|name| !halted.contains(*name)) {
+        run_passives(game, item_name, |passive| {
+            matches!(passive, Passive::Produce { .. })
+        });
+    }
+
+    for item_name in item_names.iter().filter(|name| !halted.contains(*name)) {
+        let fired = run_passives(game, item_name, |passive| {
+            matches!(passive, Passive::Convert { .. })
+        });
+
+        for (fired, wanted) in fired {
+            if fired < wanted {
+                day_events.push(DayEvent::ConversionShort {
+                    day: game.day,
+                    item: item_name.clone(),
+                    fired,
+                    wanted,
+                });
+            }
+        }
+    }
+
+    return day_events;
+}
+
+// items with a higher `priority` take their turn first and
+// ties go alphabetically, so items competing for the same
+// inputs are served in the same order on every run
+pub fn processing_order(game: &GameState) -> Vec<String> {
+    let mut items = game.items.values().collect::<Vec<&Item>>();
+    items.sort_by(|a, b| {
+        b.priority
+            .cmp(&a.priority)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    return items.into_iter().map(|item| item.name.clone()).collect();
+}
+
+// returns whether the item is halted for the day
+fn pay_upkeep(game: &mut GameState, item_name: &str, day_events: &mut Vec<DayEvent>) -> bool {
+    let item = game.items[item_name].clone();
+    if !item.amount.is_positive() {
+        return false;
+    }
+
+    let mut halted = false;
+    for passive in &item.actions_passive {
+        let Passive::Reduce { shortfall, .. } = passive else {
+            continue;
+        };
+
+        let currency_before = game.currency;
+        match passive.activate(item_name.to_string(), game, item.amount.count()) {
+            Ok(_) if game.currency.is_negative() && game.currency < currency_before => {
+                day_events.push(DayEvent::UpkeepDebt {
+                    day: game.day,
+                    item: item_name.to_string(),
+                    debt: Amount::ZERO.saturating_sub(game.currency),
+                });
+            }
+            Ok(_) => {}
+            Err(reason) => {
+                let decayed = *shortfall == Shortfall::Decay;
+                if decayed {
+                    let item = game.items.get_mut(item_name).unwrap();
+                    if let Ok(amount) = item.amount.checked_sub(Amount::from(1)) {
+                        item.amount = amount;
+                    }
+                }
+
+                halted = true;
+                day_events.push(DayEvent::UpkeepUnpaid {
+                    day: game.day,
+                    item: item_name.to_string(),
+                    reason,
+                    decayed,
+                });
+            }
+        }
+    }
+
+    return halted;
+}
+
+// runs the passives of an item picked by `filter` as often as
+// they can, up to once per owned unit. Returns how often
+// each ran and how often it should have
+fn run_passives(
+    game: &mut GameState,
+    item_name: &str,
+    filter: impl Fn(&Passive) -> bool,
+) -> Vec<(i32, i32)> {
+    let item = game.items[item_name].clone();
+    if !item.amount.is_positive() {
+        return Vec::new();
+    }
+    let owned = item.amount.count();
+
+    let mut fired = Vec::new();
+    for passive in item
+        .actions_passive
+        .iter()
+        .filter(|passive| filter(passive))
+    {
+        let times = passive.max_activate(item_name.to_string(), game).min(owned);
+
+        // items the scenario never defined
+        // are caught by validation
+        if times > 0 && passive.activate(item_name.to_string(), game, times).is_ok() {
+            fired.push((times, owned));
+        } else {
+            fired.push((0, owned));
+        }
+    }
+
+    return fired;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a Farm growing Grain that a Mill and a Bakery both
+    // turn into something, the Bakery with a higher priority
+    fn game(farm_upkeep: &str) -> GameState {
+        let scenario = format!(
+            r#"{{
+                "day": 10,
+                "currency": 0,
+                "items": {{
+                    "Grain": {{ "name": "Grain", "amount": 0, "category": "Resource",
+                        "actions_active": [], "actions_passive": [] }},
+                    "Flour": {{ "name": "Flour", "amount": 0, "category": "Resource",
+                        "actions_active": [], "actions_passive": [] }},
+                    "Bread": {{ "name": "Bread", "amount": 0, "category": "Resource",
+                        "actions_active": [], "actions_passive": [] }},
+                    "Farm": {{ "name": "Farm", "amount": 1, "category": "Building",
+                        "actions_active": [],
+                        "actions_passive": [
+                            {farm_upkeep}
+                            {{ "Produce": {{ "item_production": {{ "Grain": 2 }} }} }}
+                        ] }},
+                    "Mill": {{ "name": "Mill", "amount": 1, "category": "Building",
+                        "actions_active": [],
+                        "actions_passive": [
+                            {{ "Convert": {{ "inputs": {{ "Grain": 2 }}, "outputs": {{ "Flour": 1 }} }} }}
+                        ] }},
+                    "Bakery": {{ "name": "Bakery", "amount": 1, "category": "Building",
+                        "priority": 1,
+                        "actions_active": [],
+                        "actions_passive": [
+                            {{ "Convert": {{ "inputs": {{ "Grain": 2 }}, "outputs": {{ "Bread": 1 }} }} }}
+                        ] }}
+                }}
+            }}"#
+        );
+
+        return GameState::generate_from_json("test.json", &scenario).unwrap();
+    }
+
+    fn amount(game: &GameState, name: &str) -> i64 {
+        return game.items[name].amount.get();
+    }
+
+    #[test]
+    fn items_take_turns_by_priority_then_name() {
+        let order = processing_order(&game(""));
+
+        assert_eq!(order, ["Bakery", "Bread", "Farm", "Flour", "Grain", "Mill"]);
+    }
+
+    #[test]
+    fn conversions_use_what_was_produced_that_day() {
+        let mut game = game("");
+        let events = pass_day(&mut game);
+
+        // only the Bakery, going first, gets the Grain
+        assert_eq!(amount(&game, "Bread"), 1);
+        assert_eq!(amount(&game, "Flour"), 0);
+        assert_eq!(amount(&game, "Grain"), 0);
+        assert!(matches!(
+            events.as_slice(),
+            [DayEvent::ConversionShort { item, fired: 0, wanted: 1, .. }] if item == "Mill"
+        ));
+    }
+
+    #[test]
+    fn upkeep_is_paid_before_production() {
+        // the upkeep could be paid from the day's
+        // Grain if production went first
+        let mut game = game(r#"{ "Reduce": { "item_reduction": { "Grain": 1 } } },"#);
+        let events = pass_day(&mut game);
+
+        assert_eq!(amount(&game, "Grain"), 0);
+        assert_eq!(amount(&game, "Bread"), 0);
+        assert!(matches!(
+            events.first(),
+            Some(DayEvent::UpkeepUnpaid { item, decayed: false, .. }) if item == "Farm"
+        ));
+    }
+}
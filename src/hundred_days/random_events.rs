@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    action::{ActionError, Changes},
+    amount::Amount,
+    game::{DayEvent, GameState},
+    storage::{self, Overflow},
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Effect {
+    AddItems { items: HashMap<String, Amount> },
+    // removes up to the given amounts, never below zero
+    RemoveItems { items: HashMap<String, Amount> },
+    // destroys a share of an item, rounded up
+    DestroyItems { item: String, fraction: f64 },
+    ChangeCurrency { amount: Amount },
+    // multiplies the market price of an item,
+    // which drifts back to normal over time
+    PriceShock { item: String, multiplier: f64 },
+}
+
+// something that may happen at the start of a day,
+// defined under `events` in the scenario
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RandomEvent {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    // chance of happening on any given day
+    pub probability: f64,
+    pub effects: Vec<Effect>,
+}
+
+impl Effect {
+    // effects that would take an amount out of
+    // range are skipped as a whole
+    fn apply(&self, game: &mut GameState, changes: &mut Changes) -> Result<(), ActionError> {
+        let mut effect_changes = Changes::default();
+        match self {
+            // what does not fit in storage is lost, checked
+            // up front so either all or none is added
+            Effect::AddItems { items } => {
+                let mut added = items
+                    .iter()
+                    .filter(|(name, _)| game.items.contains_key(*name))
+                    .collect::<Vec<(&String, &Amount)>>();
+                added.sort();
+
+                for (name, amount) in &added {
+                    game.items[*name].amount.checked_add(**amount)?;
+                }
+                for (name, amount) in added {
+                    storage::store(game, name, *amount, Overflow::Waste, changes)?;
+                }
+                return Ok(());
+            }
+            Effect::RemoveItems { items } => {
+                for (name, amount) in items {
+                    if let Some(item) = game.items.get(name) {
+                        let removed = (*amount).min(item.amount).max(Amount::ZERO);
+                        effect_changes.add_item(name, removed.checked_neg()?)?;
+                    }
+                }
+            }
+            Effect::DestroyItems { item, fraction } => {
+                if let Some(item) = game.items.get(item) {
+                    let destroyed = Amount::from_f64((item.amount.as_f64() * fraction).ceil())?
+                        .min(item.amount)
+                        .max(Amount::ZERO);
+                    effect_changes.add_item(&item.name, destroyed.checked_neg()?)?;
+                }
+            }
+            Effect::ChangeCurrency { amount } => {
+                effect_changes.add_currency(*amount)?;
+            }
+            Effect::PriceShock { item, multiplier } => {
+                if let Some(market_item) = game.market.get_mut(item) {
+                    market_item.multiplier = market_item.clamp(market_item.multiplier * multiplier);
+                }
+            }
+        }
+
+        let mut merged = changes.clone();
+        merged.add_currency(effect_changes.currency)?;
+        for (name, delta) in &effect_changes.items {
+            merged.add_item(name, *delta)?;
+        }
+        effect_changes.apply(game)?;
+        *changes = merged;
+
+        return Ok(());
+    }
+}
+
+// rolls every event of the scenario once, in the order
+// they are defined, and applies the ones that happen
+pub fn pass_day(game: &mut GameState) -> Vec<DayEvent> {
+    let mut fired = Vec::new();
+
+    for event in game.events.clone() {
+        if !game.rng.chance(event.probability) {
+            continue;
+        }
+
+        let mut changes = Changes::default();
+        for effect in &event.effects {
+            // the other effects of the event still happen
+            let _ = effect.apply(game, &mut changes);
+        }
+        changes.items.retain(|_, delta| *delta != Amount::ZERO);
+
+        fired.push(DayEvent::RandomEvent {
+            day: game.day,
+            name: event.name,
+            description: event.description,
+            changes,
+        });
+    }
+
+    return fired;
+}
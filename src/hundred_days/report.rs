@@ -0,0 +1,203 @@
+use std::collections::BTreeSet;
+
+use serde::Serialize;
+
+use super::{
+    amount::Amount,
+    game::GameState,
+    objective::{Objective, ObjectiveStatus},
+    statistics::Statistics,
+    valuation::{self, ItemValue},
+};
+
+// change of net worth over a single day
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct DayResult {
+    pub day: i32,
+    pub change: Amount,
+}
+
+// summary of a finished game
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Report {
+    pub days_played: i32,
+    // plays the game out the same way again
+    pub seed: u64,
+    pub objective: Objective,
+    pub objective_status: ObjectiveStatus,
+    pub net_worth: Amount,
+    pub currency: Amount,
+    pub items: Vec<ItemValue>,
+    pub statistics: Statistics,
+    pub best_day: Option<DayResult>,
+    pub worst_day: Option<DayResult>,
+    // best net worth of earlier games of the
+    // same scenario and seed, if any
+    pub personal_best: Option<Amount>,
+}
+
+impl Report {
+    pub fn new(game: &GameState, personal_best: Option<Amount>) -> Self {
+        let valuation = valuation::valuate(game);
+
+        // a day's result is how much the net worth
+        // changed from its start to the next day's
+        let days = game
+            .snapshots
+            .windows(2)
+            .map(|pair| DayResult {
+                day: pair[0].day,
+                change: pair[1].net_worth.saturating_sub(pair[0].net_worth),
+            })
+            .collect::<Vec<DayResult>>();
+
+        return Report {
+            days_played: days.len() as i32,
+            seed: game.seed,
+            objective: game.objective.clone(),
+            objective_status: game.objective_status,
+            net_worth: valuation.total(),
+            currency: valuation.currency,
+            items: valuation.items,
+            statistics: game.statistics.clone(),
+            best_day: days.iter().copied().max_by_key(|day| day.change),
+            worst_day: days.iter().copied().min_by_key(|day| day.change),
+            personal_best,
+        };
+    }
+
+    pub fn is_personal_best(&self) -> bool {
+        return self.personal_best.is_none_or(|best| self.net_worth > best);
+    }
+
+    pub fn to_json(&self) -> String {
+        return serde_json::to_string_pretty(self).expect("A report should always serialize");
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = String::from("# Hundred Days report\n\n");
+
+        if self.objective.has_target() {
+            markdown += &format!(
+                "- Objective: {}, {}\n",
+                self.objective, self.objective_status
+            );
+        }
+        markdown += &format!("- Net worth: {}\n", self.net_worth);
+        markdown += &format!("- Money: {}\n", self.currency);
+        markdown += &format!("- Days played: {}\n", self.days_played);
+        markdown += &format!("- Seed: {}\n", self.seed);
+        match self.personal_best {
+            Some(best) if self.is_personal_best() => {
+                markdown += &format!("- Personal best: {best}, beaten\n")
+            }
+            Some(best) => markdown += &format!("- Personal best: {best}\n"),
+            None => {}
+        }
+        if let Some(best) = self.best_day {
+            markdown += &format!("- Best day: day {} ({:+})\n", best.day, best.change);
+        }
+        if let Some(worst) = self.worst_day {
+            markdown += &format!("- Worst day: day {} ({:+})\n", worst.day, worst.change);
+        }
+
+        markdown += "\n## Net worth\n\n| Item | Amount | Value |\n| --- | ---: | ---: |\n";
+        markdown += &format!("| Money | | {} |\n", self.currency);
+        for item in self.items.iter().filter(|item| item.amount.is_positive()) {
+            let value = if item.sellable {
+                item.value.to_string()
+            } else {
+                "cannot be sold".to_string()
+            };
+            markdown += &format!("| {} | {} | {value} |\n", item.name, item.amount);
+        }
+
+        markdown += "\n## Trades\n\n";
+        markdown += "| Item | Bought | Sold | Constructed | Deconstructed |\n";
+        markdown += "| --- | ---: | ---: | ---: | ---: |\n";
+        for (name, counts) in self.trades() {
+            markdown += &format!(
+                "| {name} | {} | {} | {} | {} |\n",
+                counts[0], counts[1], counts[2], counts[3]
+            );
+        }
+        markdown += &format!(
+            "\nSpent {}, earned {}, took {} night market offer(s).\n",
+            self.statistics.spent, self.statistics.earned, self.statistics.offers_taken
+        );
+
+        return markdown;
+    }
+
+    // bought, sold, constructed and deconstructed
+    // units of every item that had any of them
+    pub fn trades(&self) -> Vec<(String, [Amount; 4])> {
+        let statistics = &self.statistics;
+        let totals = [
+            &statistics.bought,
+            &statistics.sold,
+            &statistics.constructed,
+            &statistics.deconstructed,
+        ];
+
+        let names = totals
+            .iter()
+            .flat_map(|total| total.keys())
+            .collect::<BTreeSet<&String>>();
+
+        return names
+            .into_iter()
+            .map(|name| {
+                let counts = totals.map(|total| total.get(name).copied().unwrap_or_default());
+                (name.clone(), counts)
+            })
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hundred_days::snapshot::Snapshot;
+
+    #[test]
+    fn only_a_higher_net_worth_beats_the_personal_best() {
+        let game = GameState::generate_default();
+        let net_worth = Report::new(&game, None).net_worth;
+        let lower = net_worth.checked_sub(Amount::new(1)).unwrap();
+
+        assert!(Report::new(&game, None).is_personal_best());
+        assert!(Report::new(&game, Some(lower)).is_personal_best());
+        // ties keep the earlier result
+        assert!(!Report::new(&game, Some(net_worth)).is_personal_best());
+
+        let markdown = Report::new(&game, Some(lower)).to_markdown();
+        assert!(markdown.contains(&format!("- Personal best: {lower}, beaten\n")));
+    }
+
+    #[test]
+    fn best_and_worst_days_compare_each_day_to_the_next() {
+        let mut game = GameState::generate_default();
+        let first = Snapshot::take(&game);
+        game.snapshots = [(5, 10), (4, 15), (3, 12), (2, 20)]
+            .into_iter()
+            .map(|(day, net_worth)| Snapshot {
+                day,
+                net_worth: Amount::new(net_worth),
+                ..first.clone()
+            })
+            .collect();
+
+        let report = Report::new(&game, None);
+
+        assert_eq!(report.days_played, 3);
+        let result = |day, change| {
+            Some(DayResult {
+                day,
+                change: Amount::new(change),
+            })
+        };
+        assert_eq!(report.best_day, result(3, 8));
+        assert_eq!(report.worst_day, result(4, -3));
+    }
+}
@@ -0,0 +1,359 @@
+use std::{collections::HashMap, fmt};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    action::{active::Active, check_materials, Action, ActionError, Changes},
+    amount::{Amount, AmountOverflow},
+    game::{DayEvent, GameState},
+};
+
+// something that can be researched, defined under `techs`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Tech {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    // paid when the research starts
+    #[serde(default)]
+    pub currency_cost: Amount,
+    #[serde(default)]
+    pub item_cost: HashMap<String, Amount>,
+    // days until it is done, 0 finishes right away
+    #[serde(default)]
+    pub days: i32,
+    // techs that have to be researched first
+    #[serde(default)]
+    pub requires: Vec<String>,
+    #[serde(default)]
+    pub unlocks: Vec<Unlock>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Unlock {
+    // the item is hidden and cannot be
+    // used until the tech is researched
+    Item { item: String },
+    // adds an active action to an item
+    Action { item: String, action: Active },
+    // replaces the action of the same kind on an item
+    ReplaceAction { item: String, action: Active },
+}
+
+// the tech being researched
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Project {
+    pub tech: String,
+    pub days_left: i32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Research {
+    // in the order they were finished
+    pub researched: Vec<String>,
+    // only one tech is researched at a time
+    pub current: Option<Project>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResearchError {
+    UnknownTech(String),
+    AlreadyResearched(String),
+    MissingPrerequisite { tech: String, requires: String },
+    // another tech is still being researched
+    Busy(String),
+    Cost(ActionError),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TechStatus {
+    Researched,
+    InProgress { days_left: i32 },
+    Available,
+    // a prerequisite is missing
+    Locked,
+}
+
+impl Research {
+    pub fn is_researched(&self, tech: &str) -> bool {
+        return self.researched.iter().any(|name| name == tech);
+    }
+}
+
+pub fn tech<'a>(game: &'a GameState, name: &str) -> Option<&'a Tech> {
+    return game.techs.iter().find(|tech| tech.name == name);
+}
+
+pub fn status(game: &GameState, tech: &Tech) -> TechStatus {
+    let research = &game.research;
+    if research.is_researched(&tech.name) {
+        return TechStatus::Researched;
+    }
+
+    match &research.current {
+        Some(project) if project.tech == tech.name => TechStatus::InProgress {
+            days_left: project.days_left,
+        },
+        _ if tech
+            .requires
+            .iter()
+            .all(|required| research.is_researched(required)) =>
+        {
+            TechStatus::Available
+        }
+        _ => TechStatus::Locked,
+    }
+}
+
+// number of prerequisites in the longest chain before
+// the tech, used to draw the techs as a tree
+pub fn depth(game: &GameState, tech: &Tech) -> usize {
+    // validation rules out cycles, the limit
+    // only guards against a hand edited save
+    fn depth_of(game: &GameState, tech: &Tech, limit: usize) -> usize {
+        if limit == 0 {
+            return 0;
+        }
+
+        return tech
+            .requires
+            .iter()
+            .filter_map(|required| self::tech(game, required))
+            .map(|required| depth_of(game, required, limit - 1) + 1)
+            .max()
+            .unwrap_or(0);
+    }
+
+    return depth_of(game, tech, game.techs.len());
+}
+
+// items stay hidden until a tech unlocking them is researched
+pub fn is_locked(game: &GameState, item_name: &str) -> bool {
+    return game.techs.iter().any(|tech| {
+        !game.research.is_researched(&tech.name)
+            && tech.unlocks.iter().any(|unlock| match unlock {
+                Unlock::Item { item } => item == item_name,
+                _ => false,
+            })
+    });
+}
+
+// pays for the tech and starts researching it,
+// techs that take no days are finished right away
+pub fn start(game: &mut GameState, name: &str) -> Result<Changes, ResearchError> {
+    let Some(tech) = tech(game, name).cloned() else {
+        return Err(ResearchError::UnknownTech(name.to_string()));
+    };
+
+    match status(game, &tech) {
+        TechStatus::Researched => return Err(ResearchError::AlreadyResearched(tech.name)),
+        TechStatus::InProgress { .. } => return Err(ResearchError::Busy(tech.name)),
+        TechStatus::Available => {}
+        TechStatus::Locked => {
+            let requires = tech
+                .requires
+                .iter()
+                .find(|required| !game.research.is_researched(required))
+                .cloned()
+                .unwrap_or_default();
+            return Err(ResearchError::MissingPrerequisite {
+                tech: tech.name,
+                requires,
+            });
+        }
+    }
+    if let Some(project) = &game.research.current {
+        return Err(ResearchError::Busy(project.tech.clone()));
+    }
+
+    if tech.currency_cost > game.currency {
+        return Err(ResearchError::Cost(ActionError::InsufficientFunds {
+            needed: tech.currency_cost,
+            available: game.currency,
+        }));
+    }
+    check_materials(game, &tech.item_cost, 1).map_err(ResearchError::Cost)?;
+
+    let changes = cost(&tech).map_err(|error| ResearchError::Cost(error.into()))?;
+    changes.apply(game).map_err(ResearchError::Cost)?;
+
+    game.research.current = Some(Project {
+        tech: tech.name.clone(),
+        days_left: tech.days,
+    });
+    if tech.days <= 0 {
+        finish(game);
+    }
+
+    return Ok(changes);
+}
+
+fn cost(tech: &Tech) -> Result<Changes, AmountOverflow> {
+    let mut changes = Changes::default();
+    changes.add_currency(tech.currency_cost.checked_neg()?)?;
+    for (name, cost) in &tech.item_cost {
+        changes.add_item(name, cost.checked_neg()?)?;
+    }
+
+    return Ok(changes);
+}
+
+// counts down the current research, returning
+// the event for it if it was finished
+pub fn pass_day(game: &mut GameState) -> Option<DayEvent> {
+    let project = game.research.current.as_mut()?;
+    project.days_left -= 1;
+    if project.days_left > 0 {
+        return None;
+    }
+
+    let tech = finish(game)?;
+    return Some(DayEvent::ResearchFinished {
+        day: game.day,
+        tech,
+    });
+}
+
+fn finish(game: &mut GameState) -> Option<String> {
+    let project = game.research.current.take()?;
+    let tech = tech(game, &project.tech)?.clone();
+
+    for unlock in &tech.unlocks {
+        match unlock {
+            Unlock::Item { .. } => {}
+            Unlock::Action { item, action } => {
+                if let Some(item) = game.items.get_mut(item) {
+                    item.actions_active.push(action.clone());
+                }
+            }
+            Unlock::ReplaceAction { item, action } => {
+                let Some(item) = game.items.get_mut(item) else {
+                    continue;
+                };
+
+                match item
+                    .actions_active
+                    .iter_mut()
+                    .find(|active| active.name() == action.name())
+                {
+                    Some(active) => *active = action.clone(),
+                    None => item.actions_active.push(action.clone()),
+                }
+            }
+        }
+    }
+
+    game.research.researched.push(tech.name.clone());
+    return Some(tech.name);
+}
+
+impl fmt::Display for ResearchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResearchError::UnknownTech(tech) => write!(f, "There is no tech named {tech}"),
+            ResearchError::AlreadyResearched(tech) => write!(f, "{tech} is already researched"),
+            ResearchError::MissingPrerequisite { tech, requires } => {
+                write!(f, "{tech} needs {requires} to be researched first")
+            }
+            ResearchError::Busy(tech) => write!(f, "{tech} is still being researched"),
+            ResearchError::Cost(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl fmt::Display for Unlock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Unlock::Item { item } => write!(f, "Unlocks {item}"),
+            Unlock::Action { item, action } => write!(f, "Unlocks {} for {item}", action.name()),
+            Unlock::ReplaceAction { item, action } => {
+                write!(f, "Improves {} for {item}", action.name())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // researches a tech of the default scenario to the end
+    fn research(game: &mut GameState, name: &str) {
+        start(game, name).unwrap();
+        while pass_day(game).is_none() {}
+    }
+
+    fn active_names(game: &GameState, item: &str) -> Vec<String> {
+        return game.items[item]
+            .actions_active
+            .iter()
+            .map(|action| action.name().to_string())
+            .collect();
+    }
+
+    #[test]
+    fn research_is_paid_up_front_and_unlocks_items_when_done() {
+        let mut game = GameState::generate_default();
+        assert!(is_locked(&game, "Smelter"));
+
+        let changes = start(&mut game, "Ironworking").unwrap();
+        assert_eq!(changes.currency, Amount::new(-60));
+        assert_eq!(changes.items["Stone"], Amount::new(-50));
+        assert_eq!(
+            start(&mut game, "Shoring"),
+            Err(ResearchError::Busy("Ironworking".to_string()))
+        );
+
+        for _ in 0..4 {
+            assert_eq!(pass_day(&mut game), None);
+            assert!(is_locked(&game, "Smelter"));
+        }
+        assert!(matches!(
+            pass_day(&mut game),
+            Some(DayEvent::ResearchFinished { tech, .. }) if tech == "Ironworking"
+        ));
+        assert!(!is_locked(&game, "Smelter"));
+        assert_eq!(
+            start(&mut game, "Ironworking"),
+            Err(ResearchError::AlreadyResearched("Ironworking".to_string()))
+        );
+    }
+
+    #[test]
+    fn techs_need_their_prerequisites_researched() {
+        let mut game = GameState::generate_default();
+        game.currency = Amount::new(1_000);
+
+        assert!(matches!(
+            start(&mut game, "Amber trade"),
+            Err(ResearchError::MissingPrerequisite { requires, .. }) if requires == "Ironworking"
+        ));
+
+        research(&mut game, "Ironworking");
+        research(&mut game, "Shoring");
+        assert_eq!(active_names(&game, "Amber"), ["Sell"]);
+
+        research(&mut game, "Amber trade");
+        assert_eq!(active_names(&game, "Amber"), ["Sell", "Buy"]);
+    }
+
+    #[test]
+    fn replaced_actions_keep_their_place() {
+        let mut game = GameState::generate_default();
+        let names = active_names(&game, "Mine");
+
+        research(&mut game, "Shoring");
+
+        assert_eq!(active_names(&game, "Mine"), names);
+        let cost = game.items["Mine"]
+            .actions_active
+            .iter()
+            .find_map(|action| match action {
+                Active::Construct { build_cost, .. } => Some(build_cost.clone()),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(cost["Wood"], Amount::new(20));
+        assert_eq!(cost["Stone"], Amount::new(10));
+    }
+}
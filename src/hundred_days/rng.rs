@@ -0,0 +1,53 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+// small splitmix64 generator, kept in the game
+// state so saved games continue the same sequence
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        return z ^ (z >> 31);
+    }
+
+    // uniform in 0..1
+    pub fn next_f64(&mut self) -> f64 {
+        return (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+    }
+
+    // uniform in -1..1
+    pub fn next_signed(&mut self) -> f64 {
+        return self.next_f64() * 2.0 - 1.0;
+    }
+
+    pub fn chance(&mut self, probability: f64) -> bool {
+        return self.next_f64() < probability;
+    }
+
+    // uniform in 0..bound, bound must not be 0
+    pub fn below(&mut self, bound: usize) -> usize {
+        return (self.next_u64() % bound as u64) as usize;
+    }
+}
+
+// seed for scenarios and command lines that do not set one
+pub fn random_seed() -> u64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_nanos());
+
+    return Rng::new(nanos as u64 ^ (nanos >> 64) as u64).next_u64();
+}
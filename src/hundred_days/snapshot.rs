@@ -0,0 +1,30 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{amount::Amount, game::GameState, valuation};
+
+// how the game stood at the start of a day,
+// kept for every day played to chart progress
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub day: i32,
+    pub currency: Amount,
+    pub net_worth: Amount,
+    pub items: BTreeMap<String, Amount>,
+}
+
+impl Snapshot {
+    pub fn take(game: &GameState) -> Self {
+        Snapshot {
+            day: game.day,
+            currency: game.currency,
+            net_worth: valuation::valuate(game).total(),
+            items: game
+                .items
+                .values()
+                .map(|item| (item.name.clone(), item.amount))
+                .collect(),
+        }
+    }
+}
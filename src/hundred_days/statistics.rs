@@ -0,0 +1,71 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    action::{active::Active, Changes},
+    amount::Amount,
+};
+
+// running totals of what the player did,
+// shown in the report at the end of a game
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Statistics {
+    // units per item name
+    pub bought: BTreeMap<String, Amount>,
+    pub sold: BTreeMap<String, Amount>,
+    pub constructed: BTreeMap<String, Amount>,
+    pub deconstructed: BTreeMap<String, Amount>,
+    pub offers_taken: Amount,
+    // currency paid and received by
+    // actions and night market trades
+    pub spent: Amount,
+    pub earned: Amount,
+}
+
+// totals are only shown, so they saturate
+impl Statistics {
+    pub fn record_action(
+        &mut self,
+        action: &Active,
+        item_name: &str,
+        amount: i32,
+        changes: &Changes,
+    ) {
+        let totals = match action {
+            Active::Buy { .. } => &mut self.bought,
+            Active::Sell { .. } => &mut self.sold,
+            Active::Construct { .. } => &mut self.constructed,
+            Active::Deconstruct { .. } => &mut self.deconstructed,
+        };
+        let total = totals.entry(item_name.to_string()).or_default();
+        *total = total.saturating_add(amount.into());
+
+        self.record_currency(changes);
+    }
+
+    // cancelled builds were never constructed
+    pub fn record_cancel(&mut self, item_name: &str, amount: i32) {
+        let Some(total) = self.constructed.get_mut(item_name) else {
+            return;
+        };
+
+        *total = total.saturating_sub(amount.into()).max(Amount::ZERO);
+        if *total == Amount::ZERO {
+            self.constructed.remove(item_name);
+        }
+    }
+
+    pub fn record_trade(&mut self, amount: i32, changes: &Changes) {
+        self.offers_taken = self.offers_taken.saturating_add(amount.into());
+        self.record_currency(changes);
+    }
+
+    fn record_currency(&mut self, changes: &Changes) {
+        if changes.currency.is_negative() {
+            self.spent = self.spent.saturating_sub(changes.currency);
+        } else {
+            self.earned = self.earned.saturating_add(changes.currency);
+        }
+    }
+}
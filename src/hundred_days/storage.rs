@@ -0,0 +1,224 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+    action::{ActionError, Changes},
+    amount::Amount,
+    game::GameState,
+    market, valuation,
+};
+
+// what happens to production that does not fit in storage
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum Overflow {
+    #[default]
+    Waste,
+    // sold right away for the current sell price less `discount`,
+    // 0.25 for a quarter off. Items that cannot be sold are wasted
+    Sell {
+        discount: f64,
+    },
+}
+
+// what every owned building adds to the capacity of
+// `target`, the name of an item or else of a category
+fn storage_bonus(game: &GameState, target: &str) -> Amount {
+    return game
+        .items
+        .values()
+        .filter_map(|item| {
+            let per_building = item.storage.get(target)?;
+            // more room than an amount holds is as good as unlimited
+            Some(
+                per_building
+                    .checked_mul(item.amount.count())
+                    .unwrap_or(Amount::MAX),
+            )
+        })
+        .sum();
+}
+
+// how much of the item can be kept on its own, none
+// for items without a `capacity` of their own
+pub fn capacity(game: &GameState, item_name: &str) -> Option<Amount> {
+    let capacity = game.items.get(item_name)?.capacity?;
+    return Some(capacity.saturating_add(storage_bonus(game, item_name)));
+}
+
+// how much all items of the category can keep together
+pub fn category_capacity(game: &GameState, category: &str) -> Option<Amount> {
+    let capacity = game
+        .categories
+        .iter()
+        .find(|known| known.name == category)?
+        .capacity?;
+
+    // items take precedence for storage named after both
+    if game.items.contains_key(category) {
+        return Some(capacity);
+    }
+    return Some(capacity.saturating_add(storage_bonus(game, category)));
+}
+
+// owned plus queued buildings, which
+// take up room once they are finished
+fn stored(game: &GameState, item_name: &str) -> Amount {
+    let queued = game
+        .build_queue
+        .iter()
+        .filter(|build| build.item == item_name)
+        .map(|build| Amount::from(build.amount))
+        .sum::<Amount>();
+
+    return game
+        .items
+        .get(item_name)
+        .map_or(Amount::ZERO, |item| item.amount)
+        .saturating_add(queued);
+}
+
+fn item_space(game: &GameState, item_name: &str) -> Option<Amount> {
+    let capacity = capacity(game, item_name)?;
+    return Some(capacity.saturating_sub(stored(game, item_name)));
+}
+
+fn category_space(game: &GameState, category: &str) -> Option<Amount> {
+    let capacity = category_capacity(game, category)?;
+    let used = game
+        .items
+        .values()
+        .filter(|item| item.category == category)
+        .map(|item| stored(game, &item.name))
+        .sum::<Amount>();
+
+    return Some(capacity.saturating_sub(used));
+}
+
+// room left for the item under its own capacity and
+// that of its category, none when neither limits it
+pub fn space(game: &GameState, item_name: &str) -> Option<Amount> {
+    let item = game.items.get(item_name)?;
+
+    let space = match (
+        item_space(game, item_name),
+        category_space(game, &item.category),
+    ) {
+        (Some(item_space), Some(category_space)) => item_space.min(category_space),
+        (space, None) | (None, space) => space?,
+    };
+    return Some(space.max(Amount::ZERO));
+}
+
+// every item and category `changes` adds to, with what is
+// added and the room left. Categories count what is taken
+// out of them against what is added and are named after
+// the first item adding to them
+fn limits<'a>(game: &GameState, changes: &'a Changes) -> Vec<(&'a String, Amount, Amount)> {
+    let mut limits = Vec::new();
+    let mut categories = Vec::new();
+
+    for (name, added) in &changes.items {
+        let Some(item) = game.items.get(name) else {
+            continue;
+        };
+        if !added.is_positive() {
+            continue;
+        }
+
+        if let Some(room) = item_space(game, name) {
+            limits.push((name, *added, room.max(Amount::ZERO)));
+        }
+
+        if categories.contains(&&item.category) {
+            continue;
+        }
+        categories.push(&item.category);
+
+        let category_added = changes
+            .items
+            .iter()
+            .filter(|(other, _)| {
+                game.items
+                    .get(*other)
+                    .is_some_and(|other| other.category == item.category)
+            })
+            .map(|(_, delta)| *delta)
+            .sum::<Amount>();
+        if let Some(room) = category_space(game, &item.category) {
+            if category_added.is_positive() {
+                limits.push((name, category_added, room.max(Amount::ZERO)));
+            }
+        }
+    }
+
+    return limits;
+}
+
+// how often `changes` can be made before running
+// out of storage, `i32::MAX` when nothing limits it
+pub fn times_fitting(game: &GameState, changes: &Changes) -> i32 {
+    return limits(game, changes)
+        .into_iter()
+        .map(|(_, added, room)| room.times(added))
+        .min()
+        .unwrap_or(i32::MAX);
+}
+
+// errors with the first item that `changes` adds more of than fits
+pub fn check(game: &GameState, changes: &Changes) -> Result<(), ActionError> {
+    let full = limits(game, changes)
+        .into_iter()
+        .find(|(_, added, room)| added > room);
+
+    return match full {
+        Some((item, requested, space)) => Err(ActionError::StorageFull {
+            item: item.clone(),
+            requested,
+            space,
+        }),
+        None => Ok(()),
+    };
+}
+
+// how many of `amount` fit, everything when storage is unlimited
+pub fn fitting(game: &GameState, item_name: &str, amount: Amount) -> Amount {
+    return space(game, item_name).map_or(amount, |space| space.min(amount));
+}
+
+// adds `amount` of the item, up to what fits. The rest is
+// wasted or sold, as `overflow` says, and `changes` has both.
+// Nothing is stored or sold when the result would not fit
+pub fn store(
+    game: &mut GameState,
+    item_name: &str,
+    amount: Amount,
+    overflow: Overflow,
+    changes: &mut Changes,
+) -> Result<(), ActionError> {
+    if !game.items.contains_key(item_name) {
+        return Ok(());
+    }
+
+    // negative amounts are taken out in full, storage never limits that
+    let stored = fitting(game, item_name, amount);
+    let mut stored_changes = Changes::default();
+    stored_changes.add_item(item_name, stored)?;
+
+    let excess = amount.checked_sub(stored)?;
+    if let (Overflow::Sell { discount }, Some(sell_price)) =
+        (overflow, valuation::sell_price(game, item_name))
+    {
+        if excess.is_positive() {
+            let price = market::current_price(game, item_name, sell_price)?;
+            let earnings = Amount::from_f64(price.as_f64() * excess.as_f64() * (1.0 - discount))?;
+            stored_changes.add_currency(earnings)?;
+        }
+    }
+
+    let mut merged = changes.clone();
+    merged.add_item(item_name, stored)?;
+    merged.add_currency(stored_changes.currency)?;
+    stored_changes.apply(game)?;
+    *changes = merged;
+
+    return Ok(());
+}
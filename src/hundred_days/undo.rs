@@ -0,0 +1,71 @@
+use std::collections::VecDeque;
+
+use super::game::GameState;
+
+pub const DEFAULT_UNDO_DEPTH: usize = 50;
+
+// snapshots of the game taken before each change,
+// the oldest are dropped once `depth` is reached
+pub struct UndoStack {
+    undo: VecDeque<GameState>,
+    redo: Vec<GameState>,
+    depth: usize,
+}
+
+impl Default for UndoStack {
+    fn default() -> Self {
+        return UndoStack::new(DEFAULT_UNDO_DEPTH);
+    }
+}
+
+impl UndoStack {
+    pub fn new(depth: usize) -> Self {
+        UndoStack {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            depth,
+        }
+    }
+
+    // records the state from before a change,
+    // which makes anything undone unrecoverable
+    pub fn record(&mut self, before: GameState) {
+        if self.depth == 0 {
+            return;
+        }
+
+        self.redo.clear();
+        self.undo.push_back(before);
+
+        while self.undo.len() > self.depth {
+            self.undo.pop_front();
+        }
+    }
+
+    pub fn undo(&mut self, current: &GameState) -> Option<GameState> {
+        let previous = self.undo.pop_back()?;
+        self.redo.push(current.clone());
+
+        return Some(previous);
+    }
+
+    pub fn redo(&mut self, current: &GameState) -> Option<GameState> {
+        let next = self.redo.pop()?;
+        self.undo.push_back(current.clone());
+
+        return Some(next);
+    }
+
+    pub fn can_undo(&self) -> bool {
+        return !self.undo.is_empty();
+    }
+
+    pub fn can_redo(&self) -> bool {
+        return !self.redo.is_empty();
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
@@ -0,0 +1,1496 @@
+use std::{collections::HashMap, error::Error, fmt, io, path::PathBuf};
+
+use serde_json::{Map, Value};
+
+use super::{
+    game::GameState,
+    market::{default_ceiling, default_floor, MIN_SLIPPAGE},
+};
+
+const ITEM_FIELDS: [&str; 9] = [
+    "name",
+    "amount",
+    "category",
+    "industries",
+    "priority",
+    "capacity",
+    "storage",
+    "actions_active",
+    "actions_passive",
+];
+const ACTIVE_ACTIONS: [&str; 4] = ["Buy", "Sell", "Construct", "Deconstruct"];
+const PASSIVE_ACTIONS: [&str; 3] = ["Produce", "Reduce", "Convert"];
+const SHORTFALLS: [&str; 3] = ["Halt", "Decay", "Debt"];
+const DIFFICULTIES: [&str; 3] = ["Easy", "Normal", "Hard"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationError {
+    // dotted path to the offending value,
+    // such as `items.Mine.actions_active[0]`
+    pub path: String,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}:{}: {}", self.line, self.column, self.message)
+        } else {
+            write!(
+                f,
+                "{}:{}: {}: {}",
+                self.line, self.column, self.path, self.message
+            )
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationReport {
+    pub source_name: String,
+    pub errors: Vec<ValidationError>,
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} has {} problem(s):",
+            self.source_name,
+            self.errors.len()
+        )?;
+
+        for error in &self.errors {
+            write!(f, "\n  {}:{error}", self.source_name)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Error for ValidationReport {}
+
+#[derive(Debug)]
+pub enum ScenarioError {
+    Io { path: PathBuf, error: io::Error },
+    Invalid(ValidationReport),
+}
+
+impl fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScenarioError::Io { path, error } => {
+                write!(f, "Could not read scenario {}: {error}", path.display())
+            }
+            ScenarioError::Invalid(report) => write!(f, "{report}"),
+        }
+    }
+}
+
+impl Error for ScenarioError {}
+
+// checks the scenario as a whole and collects every
+// problem instead of stopping at the first one
+pub fn validate_scenario(source_name: &str, contents: &str) -> Result<GameState, ValidationReport> {
+    let report = |errors| ValidationReport {
+        source_name: source_name.to_string(),
+        errors,
+    };
+
+    let root: Value = match serde_json::from_str(contents) {
+        Ok(root) => root,
+        Err(err) => {
+            return Err(report(vec![ValidationError {
+                path: String::new(),
+                line: err.line(),
+                column: err.column(),
+                message: err.to_string(),
+            }]))
+        }
+    };
+
+    let mut validator = Validator {
+        locations: locate_values(contents),
+        errors: Vec::new(),
+    };
+    validator.scenario(&root);
+
+    if !validator.errors.is_empty() {
+        return Err(report(validator.errors));
+    }
+
+    // anything the checks above missed is still reported with
+    // the position serde gives us, which a `Value` has lost
+    return serde_json::from_str(contents).map_err(|err| {
+        report(vec![ValidationError {
+            path: String::new(),
+            line: err.line(),
+            column: err.column(),
+            message: err.to_string(),
+        }])
+    });
+}
+
+struct Validator {
+    locations: Locations,
+    errors: Vec<ValidationError>,
+}
+
+impl Validator {
+    fn error(&mut self, path: &str, message: String) {
+        let (line, column) = self.locations.find(path);
+
+        self.errors.push(ValidationError {
+            path: path.to_string(),
+            line,
+            column,
+            message,
+        });
+    }
+
+    fn scenario(&mut self, root: &Value) {
+        for (path, (line, column)) in self.locations.duplicates.clone() {
+            self.errors.push(ValidationError {
+                path,
+                line,
+                column,
+                message: "duplicate key".to_string(),
+            });
+        }
+
+        let Some(root) = root.as_object() else {
+            self.error("", "scenario must be a JSON object".to_string());
+            return;
+        };
+
+        self.integer(root, "", "day", false);
+        self.amount(root, "", "currency", false);
+        if root.contains_key("builders") {
+            self.integer(root, "", "builders", true);
+        }
+        if root
+            .get("hardcore")
+            .is_some_and(|hardcore| !hardcore.is_boolean())
+        {
+            self.error("hardcore", "expected true or false".to_string());
+        }
+        for field in ["title", "author", "description"] {
+            if root.get(field).is_some_and(|text| !text.is_string()) {
+                self.error(field, "expected a string".to_string());
+            }
+        }
+        if let Some(difficulty) = root.get("difficulty") {
+            if !difficulty
+                .as_str()
+                .is_some_and(|difficulty| DIFFICULTIES.contains(&difficulty))
+            {
+                self.error(
+                    "difficulty",
+                    format!("difficulty must be one of {}", DIFFICULTIES.join(", ")),
+                );
+            }
+        }
+
+        let Some(items) = self.field(root, "", "items") else {
+            return;
+        };
+        let Some(items) = items.as_object() else {
+            self.error("items", "expected an object of items".to_string());
+            return;
+        };
+
+        // without a list of categories
+        // items may use any category
+        let categories = root
+            .get("categories")
+            .map(|categories| self.categories(categories));
+        for (key, item) in items {
+            self.item(items, categories.as_deref(), key, item);
+        }
+
+        // storage can name any category, including
+        // ones only used by items
+        let mut category_names = categories.unwrap_or_default();
+        category_names.extend(
+            items
+                .values()
+                .filter_map(|item| Some(item.get("category")?.as_str()?.to_string())),
+        );
+        for (key, item) in items {
+            if let Some(storage) = item.get("storage") {
+                self.storage(items, &category_names, &format!("items.{key}"), storage);
+            }
+        }
+
+        if let Some(market) = root.get("market") {
+            self.market(items, market);
+        }
+        if let Some(night_market) = root.get("night_market") {
+            self.night_market(items, night_market);
+        }
+        if let Some(events) = root.get("events") {
+            self.events(items, root, events);
+        }
+        if let Some(objective) = root.get("objective") {
+            self.objective(items, objective);
+        }
+        if let Some(synergies) = root.get("synergies") {
+            self.synergies(items, synergies);
+        }
+        if let Some(techs) = root.get("techs") {
+            self.techs(items, techs);
+        }
+        if root.get("seed").is_some_and(|seed| !seed.is_u64()) {
+            self.error("seed", "expected a whole number".to_string());
+        }
+    }
+
+    fn events(&mut self, items: &Map<String, Value>, root: &Map<String, Value>, events: &Value) {
+        let Some(events) = events.as_array() else {
+            self.error("events", "expected a list of events".to_string());
+            return;
+        };
+
+        let market = root.get("market").and_then(Value::as_object);
+
+        for (i, event) in events.iter().enumerate() {
+            let path = format!("events[{i}]");
+            let Some(event) = event.as_object() else {
+                self.error(&path, "expected an event object".to_string());
+                continue;
+            };
+
+            if let Some(name) = self.field(event, &path, "name") {
+                if !name.is_string() {
+                    self.error(&format!("{path}.name"), "expected a string".to_string());
+                }
+            }
+
+            if let Some(probability) = self.field(event, &path, "probability") {
+                if !probability
+                    .as_f64()
+                    .is_some_and(|probability| (0.0..=1.0).contains(&probability))
+                {
+                    self.error(
+                        &format!("{path}.probability"),
+                        "expected a number from 0 to 1".to_string(),
+                    );
+                }
+            }
+
+            let Some(effects) = self.field(event, &path, "effects") else {
+                continue;
+            };
+            let Some(effects) = effects.as_array() else {
+                self.error(&format!("{path}.effects"), "expected a list".to_string());
+                continue;
+            };
+
+            for (j, effect) in effects.iter().enumerate() {
+                let effect_path = format!("{path}.effects[{j}]");
+                self.effect(items, market, &effect_path, effect);
+            }
+        }
+    }
+
+    fn effect(
+        &mut self,
+        items: &Map<String, Value>,
+        market: Option<&Map<String, Value>>,
+        path: &str,
+        effect: &Value,
+    ) {
+        let single_entry = effect.as_object().filter(|effect| effect.len() == 1);
+        let Some((effect_name, fields)) = single_entry.and_then(|effect| effect.iter().next())
+        else {
+            self.error(
+                path,
+                "expected an object with a single effect name".to_string(),
+            );
+            return;
+        };
+
+        let path = format!("{path}.{effect_name}");
+        let Some(fields) = fields.as_object() else {
+            self.error(&path, "expected an object of effect fields".to_string());
+            return;
+        };
+
+        let number = |field: &str| fields.get(field).and_then(Value::as_f64);
+
+        match effect_name.as_str() {
+            "AddItems" | "RemoveItems" => self.item_amounts(items, fields, &path, "items", true),
+            "ChangeCurrency" => {
+                if !self
+                    .field(fields, &path, "amount")
+                    .is_some_and(Value::is_i64)
+                {
+                    self.error(
+                        &format!("{path}.amount"),
+                        "expected a whole number".to_string(),
+                    );
+                }
+            }
+            "DestroyItems" => {
+                self.item_name(items, fields, &path);
+                if !number("fraction").is_some_and(|fraction| (0.0..=1.0).contains(&fraction)) {
+                    self.error(
+                        &format!("{path}.fraction"),
+                        "expected a number from 0 to 1".to_string(),
+                    );
+                }
+            }
+            "PriceShock" => {
+                if let Some(item) = self.item_name(items, fields, &path) {
+                    if !market.is_some_and(|market| market.contains_key(&item)) {
+                        self.error(
+                            &format!("{path}.item"),
+                            format!("`{item}` has no entry under `market`, so its price is fixed"),
+                        );
+                    }
+                }
+                if !number("multiplier").is_some_and(|multiplier| multiplier > 0.0) {
+                    self.error(
+                        &format!("{path}.multiplier"),
+                        "expected a positive number".to_string(),
+                    );
+                }
+            }
+            _ => self.error(
+                &path,
+                format!(
+                    "unknown effect `{effect_name}`, expected one of AddItems, RemoveItems, \
+                     DestroyItems, ChangeCurrency, PriceShock"
+                ),
+            ),
+        }
+    }
+
+    fn synergies(&mut self, items: &Map<String, Value>, synergies: &Value) {
+        let Some(synergies) = synergies.as_array() else {
+            self.error("synergies", "expected a list of synergies".to_string());
+            return;
+        };
+
+        for (i, synergy) in synergies.iter().enumerate() {
+            let path = format!("synergies[{i}]");
+            let Some(synergy) = synergy.as_object() else {
+                self.error(&path, "expected a synergy object".to_string());
+                continue;
+            };
+
+            if let Some(name) = self.field(synergy, &path, "name") {
+                if !name.is_string() {
+                    self.error(&format!("{path}.name"), "expected a string".to_string());
+                }
+            }
+
+            // a misspelled industry would
+            // silently never give its bonus
+            if let Some(industry) = self.field(synergy, &path, "industry") {
+                match industry.as_str() {
+                    Some(industry) if !item_industries(items).contains(&industry) => self.error(
+                        &format!("{path}.industry"),
+                        format!("no item is in the `{industry}` industry"),
+                    ),
+                    Some(_) => {}
+                    None => self.error(
+                        &format!("{path}.industry"),
+                        "expected an industry name".to_string(),
+                    ),
+                }
+            }
+
+            if synergy.contains_key("requires") {
+                self.item_amounts(items, synergy, &path, "requires", true);
+            }
+
+            if let Some(bonus) = self.field(synergy, &path, "production_bonus") {
+                if !bonus.as_f64().is_some_and(|bonus| bonus > -1.0) {
+                    self.error(
+                        &format!("{path}.production_bonus"),
+                        "expected a number above -1".to_string(),
+                    );
+                }
+            }
+        }
+    }
+
+    fn techs(&mut self, items: &Map<String, Value>, techs: &Value) {
+        let Some(techs) = techs.as_array() else {
+            self.error("techs", "expected a list of techs".to_string());
+            return;
+        };
+
+        let mut names = Vec::new();
+        for (i, tech) in techs.iter().enumerate() {
+            let name = tech.get("name").and_then(Value::as_str);
+            match name {
+                Some(name) if names.contains(&name) => self.error(
+                    &format!("techs[{i}].name"),
+                    format!("tech `{name}` is defined twice"),
+                ),
+                Some(name) => names.push(name),
+                None => {}
+            }
+        }
+
+        // techs whose prerequisites can all be met, anything left
+        // over is part of a cycle. Unknown techs are reported on
+        // their own so they do not count as missing here
+        let mut reachable: Vec<&str> = Vec::new();
+        loop {
+            let newly_reachable = techs
+                .iter()
+                .filter_map(|tech| {
+                    let name = tech.get("name")?.as_str()?;
+                    let ready = tech
+                        .get("requires")
+                        .and_then(Value::as_array)
+                        .into_iter()
+                        .flatten()
+                        .filter_map(Value::as_str)
+                        .all(|required| {
+                            reachable.contains(&required) || !names.contains(&required)
+                        });
+                    (ready && !reachable.contains(&name)).then_some(name)
+                })
+                .collect::<Vec<&str>>();
+            if newly_reachable.is_empty() {
+                break;
+            }
+            reachable.extend(newly_reachable);
+        }
+
+        for (i, tech) in techs.iter().enumerate() {
+            let path = format!("techs[{i}]");
+            let Some(tech) = tech.as_object() else {
+                self.error(&path, "expected a tech object".to_string());
+                continue;
+            };
+
+            if let Some(name) = self.field(tech, &path, "name") {
+                if !name.is_string() {
+                    self.error(&format!("{path}.name"), "expected a string".to_string());
+                }
+            }
+            if tech
+                .get("description")
+                .is_some_and(|description| !description.is_string())
+            {
+                self.error(
+                    &format!("{path}.description"),
+                    "expected a string".to_string(),
+                );
+            }
+            if tech.contains_key("currency_cost") {
+                self.amount(tech, &path, "currency_cost", false);
+            }
+            if tech.contains_key("days") {
+                self.integer(tech, &path, "days", false);
+            }
+            if tech.contains_key("item_cost") {
+                self.item_amounts(items, tech, &path, "item_cost", true);
+            }
+
+            match tech.get("requires").map(Value::as_array) {
+                None => {}
+                Some(None) => self.error(
+                    &format!("{path}.requires"),
+                    "expected a list of tech names".to_string(),
+                ),
+                Some(Some(requires)) => {
+                    for (j, required) in requires.iter().enumerate() {
+                        let required_path = format!("{path}.requires[{j}]");
+                        match required.as_str() {
+                            Some(required) if !names.contains(&required) => {
+                                self.error(&required_path, format!("unknown tech `{required}`"))
+                            }
+                            Some(_) => {}
+                            None => self.error(&required_path, "expected a tech name".to_string()),
+                        }
+                    }
+                }
+            }
+            let name = tech.get("name").and_then(Value::as_str);
+            if name.is_some_and(|name| !reachable.contains(&name)) {
+                self.error(
+                    &format!("{path}.requires"),
+                    "prerequisites can never all be researched, they form a cycle".to_string(),
+                );
+            }
+
+            if let Some(unlocks) = tech.get("unlocks") {
+                self.unlocks(items, &path, unlocks);
+            }
+        }
+    }
+
+    fn unlocks(&mut self, items: &Map<String, Value>, path: &str, unlocks: &Value) {
+        let path = format!("{path}.unlocks");
+        let Some(unlocks) = unlocks.as_array() else {
+            self.error(&path, "expected a list".to_string());
+            return;
+        };
+
+        for (i, unlock) in unlocks.iter().enumerate() {
+            let path = format!("{path}[{i}]");
+            let single_entry = unlock.as_object().filter(|unlock| unlock.len() == 1);
+            let Some((unlock_name, fields)) = single_entry.and_then(|unlock| unlock.iter().next())
+            else {
+                self.error(
+                    &path,
+                    "expected an object with a single unlock name".to_string(),
+                );
+                continue;
+            };
+
+            let path = format!("{path}.{unlock_name}");
+            let Some(fields) = fields.as_object() else {
+                self.error(&path, "expected an object of unlock fields".to_string());
+                continue;
+            };
+
+            self.item_name(items, fields, &path);
+            match unlock_name.as_str() {
+                "Item" => {}
+                "Action" | "ReplaceAction" => {
+                    if let Some(action) = self.field(fields, &path, "action") {
+                        self.action(items, &format!("{path}.action"), action, &ACTIVE_ACTIONS);
+                    }
+                }
+                _ => self.error(
+                    &path,
+                    format!(
+                        "unknown unlock `{unlock_name}`, expected one of Item, Action, \
+                         ReplaceAction"
+                    ),
+                ),
+            }
+        }
+    }
+
+    // either the name of an objective without fields
+    // or an object with a single objective name
+    fn objective(&mut self, items: &Map<String, Value>, objective: &Value) {
+        if objective.as_str() == Some("MaximizeNetWorth") {
+            return;
+        }
+
+        let single_entry = objective
+            .as_object()
+            .filter(|objective| objective.len() == 1);
+        let Some((objective_name, fields)) =
+            single_entry.and_then(|objective| objective.iter().next())
+        else {
+            self.error(
+                "objective",
+                "expected `MaximizeNetWorth` or an object with a single objective name".to_string(),
+            );
+            return;
+        };
+
+        let path = format!("objective.{objective_name}");
+        let Some(fields) = fields.as_object() else {
+            self.error(&path, "expected an object of objective fields".to_string());
+            return;
+        };
+
+        match objective_name.as_str() {
+            "TargetNetWorth" => {
+                self.amount(fields, &path, "target", true);
+                if fields.contains_key("by_day") {
+                    self.integer(fields, &path, "by_day", false);
+                }
+            }
+            "MaximizeItem" => {
+                self.item_name(items, fields, &path);
+                if fields.contains_key("target") {
+                    self.amount(fields, &path, "target", false);
+                }
+            }
+            _ => self.error(
+                &path,
+                format!(
+                    "unknown objective `{objective_name}`, expected one of MaximizeNetWorth, \
+                     TargetNetWorth, MaximizeItem"
+                ),
+            ),
+        }
+    }
+
+    // the `item` field of an object, which must name a known item
+    fn item_name(
+        &mut self,
+        items: &Map<String, Value>,
+        fields: &Map<String, Value>,
+        path: &str,
+    ) -> Option<String> {
+        let item = self.field(fields, path, "item")?;
+        let Some(item) = item.as_str() else {
+            self.error(&format!("{path}.item"), "expected an item name".to_string());
+            return None;
+        };
+
+        if !items.contains_key(item) {
+            self.error(&format!("{path}.item"), format!("unknown item `{item}`"));
+            return None;
+        }
+
+        return Some(item.to_string());
+    }
+
+    fn night_market(&mut self, items: &Map<String, Value>, night_market: &Value) {
+        let path = "night_market";
+        let Some(night_market) = night_market.as_object() else {
+            self.error(path, "expected an object".to_string());
+            return;
+        };
+
+        for field in ["refresh_days", "offers_shown"] {
+            if night_market.contains_key(field) {
+                self.integer(night_market, path, field, true);
+            }
+        }
+
+        let Some(offers) = self.field(night_market, path, "offers") else {
+            return;
+        };
+        let Some(offers) = offers.as_array().filter(|offers| !offers.is_empty()) else {
+            self.error(
+                "night_market.offers",
+                "expected a list of at least one offer".to_string(),
+            );
+            return;
+        };
+
+        for (i, offer) in offers.iter().enumerate() {
+            let path = format!("night_market.offers[{i}]");
+            let Some(offer) = offer.as_object() else {
+                self.error(&path, "expected an offer object".to_string());
+                continue;
+            };
+
+            if let Some(name) = self.field(offer, &path, "name") {
+                if !name.is_string() {
+                    self.error(&format!("{path}.name"), "expected a string".to_string());
+                }
+            }
+            if offer.contains_key("price") {
+                self.amount(offer, &path, "price", false);
+            }
+            if offer.contains_key("stock") {
+                self.integer(offer, &path, "stock", true);
+            }
+            if offer.contains_key("give") {
+                self.item_amounts(items, offer, &path, "give", true);
+            }
+            self.item_amounts(items, offer, &path, "receive", true);
+        }
+    }
+
+    fn market(&mut self, items: &Map<String, Value>, market: &Value) {
+        let Some(market) = market.as_object() else {
+            self.error("market", "expected an object keyed by item".to_string());
+            return;
+        };
+
+        for (item_name, fields) in market {
+            let path = format!("market.{item_name}");
+            if !items.contains_key(item_name) {
+                self.error(&path, format!("unknown item `{item_name}`"));
+            }
+
+            let Some(fields) = fields.as_object() else {
+                self.error(&path, "expected an object of market settings".to_string());
+                continue;
+            };
+
+            for (field, value) in fields {
+                let field_path = format!("{path}.{field}");
+                let Some(number) = value.as_f64().filter(|number| number.is_finite()) else {
+                    self.error(&field_path, "expected a number".to_string());
+                    continue;
+                };
+
+                let valid = match field.as_str() {
+                    "volatility" => number >= 0.0,
+                    "slippage" => number == 0.0 || (MIN_SLIPPAGE..1.0).contains(&number),
+                    "reversion" => (0.0..1.0).contains(&number),
+                    "floor" | "ceiling" | "multiplier" => number > 0.0,
+                    _ => {
+                        self.error(&field_path, format!("unknown market setting `{field}`"));
+                        continue;
+                    }
+                };
+
+                if !valid {
+                    self.error(&field_path, format!("{number} is out of range"));
+                }
+            }
+
+            // bounds left out are compared as their defaults
+            let bound = |field: &str, default: f64| match fields.get(field) {
+                Some(value) => value.as_f64(),
+                None => Some(default),
+            };
+            let floor = bound("floor", default_floor());
+            let ceiling = bound("ceiling", default_ceiling());
+            if let (Some(floor), Some(ceiling)) = (floor, ceiling) {
+                if floor > ceiling {
+                    let field = if fields.contains_key("floor") {
+                        "floor"
+                    } else {
+                        "ceiling"
+                    };
+                    self.error(
+                        &format!("{path}.{field}"),
+                        format!("floor {floor} is above the ceiling {ceiling}"),
+                    );
+                }
+            }
+        }
+    }
+
+    // returns the names of the categories
+    fn categories(&mut self, categories: &Value) -> Vec<String> {
+        let Some(categories) = categories.as_array() else {
+            self.error("categories", "expected a list of categories".to_string());
+            return Vec::new();
+        };
+
+        let mut names = Vec::new();
+        for (i, category) in categories.iter().enumerate() {
+            let path = format!("categories[{i}]");
+            let Some(category) = category.as_object() else {
+                self.error(&path, "expected a category object".to_string());
+                continue;
+            };
+
+            if category
+                .get("title")
+                .is_some_and(|title| !title.is_string())
+            {
+                self.error(&format!("{path}.title"), "expected a string".to_string());
+            }
+            if category.contains_key("capacity") {
+                self.amount(category, &path, "capacity", false);
+            }
+
+            let Some(name) = self.field(category, &path, "name") else {
+                continue;
+            };
+            match name.as_str() {
+                Some(name) if names.iter().any(|known| known == name) => self.error(
+                    &format!("{path}.name"),
+                    format!("category `{name}` is defined twice"),
+                ),
+                Some(name) => names.push(name.to_string()),
+                None => self.error(&format!("{path}.name"), "expected a string".to_string()),
+            }
+        }
+
+        return names;
+    }
+
+    fn item(
+        &mut self,
+        items: &Map<String, Value>,
+        categories: Option<&[String]>,
+        key: &str,
+        item: &Value,
+    ) {
+        let path = format!("items.{key}");
+        let Some(item) = item.as_object() else {
+            self.error(&path, "expected an item object".to_string());
+            return;
+        };
+
+        for field in item.keys() {
+            if !ITEM_FIELDS.contains(&field.as_str()) {
+                self.error(
+                    &format!("{path}.{field}"),
+                    format!("unknown item field `{field}`"),
+                );
+            }
+        }
+
+        if let Some(name) = self.field(item, &path, "name") {
+            match name.as_str() {
+                Some(name) if name != key => self.error(
+                    &format!("{path}.name"),
+                    format!("name `{name}` does not match its key `{key}`"),
+                ),
+                Some(_) => {}
+                None => self.error(&format!("{path}.name"), "expected a string".to_string()),
+            }
+        }
+
+        self.amount(item, &path, "amount", false);
+        if item.contains_key("capacity") {
+            self.amount(item, &path, "capacity", false);
+        }
+        if item
+            .get("priority")
+            .is_some_and(|priority| priority.as_i64().is_none_or(|p| i32::try_from(p).is_err()))
+        {
+            self.error(
+                &format!("{path}.priority"),
+                "expected a whole number".to_string(),
+            );
+        }
+
+        if let Some(category) = self.field(item, &path, "category") {
+            match (category.as_str(), categories) {
+                (None, _) => self.error(
+                    &format!("{path}.category"),
+                    "expected a category name".to_string(),
+                ),
+                (Some(category), Some(categories))
+                    if !categories.iter().any(|known| known == category) =>
+                {
+                    self.error(
+                        &format!("{path}.category"),
+                        format!(
+                            "unknown category `{category}`, expected one of {}",
+                            categories.join(", ")
+                        ),
+                    )
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(industries) = item.get("industries") {
+            let is_string_list = industries
+                .as_array()
+                .is_some_and(|list| list.iter().all(Value::is_string));
+
+            if !is_string_list {
+                self.error(
+                    &format!("{path}.industries"),
+                    "expected a list of industry names".to_string(),
+                );
+            }
+        }
+
+        let action_kinds: [(&str, &[&str]); 2] = [
+            ("actions_active", &ACTIVE_ACTIONS),
+            ("actions_passive", &PASSIVE_ACTIONS),
+        ];
+
+        for (field, actions) in action_kinds {
+            let Some(list) = self.field(item, &path, field) else {
+                continue;
+            };
+            let Some(list) = list.as_array() else {
+                self.error(&format!("{path}.{field}"), "expected a list".to_string());
+                continue;
+            };
+
+            for (i, action) in list.iter().enumerate() {
+                let action_path = format!("{path}.{field}[{i}]");
+                self.action(items, &action_path, action, actions);
+            }
+        }
+    }
+
+    fn action(
+        &mut self,
+        items: &Map<String, Value>,
+        path: &str,
+        action: &Value,
+        known_actions: &[&str],
+    ) {
+        let single_entry = action.as_object().filter(|action| action.len() == 1);
+        let Some((action_name, fields)) = single_entry.and_then(|action| action.iter().next())
+        else {
+            self.error(
+                path,
+                "expected an object with a single action name".to_string(),
+            );
+            return;
+        };
+
+        let path = format!("{path}.{action_name}");
+        let Some(fields) = fields.as_object() else {
+            self.error(&path, "expected an object of action fields".to_string());
+            return;
+        };
+
+        match action_name.as_str() {
+            "Buy" => self.amount(fields, &path, "buy_price", true),
+            "Sell" => self.amount(fields, &path, "sell_price", true),
+            "Construct" => {
+                self.item_amounts(items, fields, &path, "build_cost", true);
+                if fields.contains_key("build_days") {
+                    self.integer(fields, &path, "build_days", false);
+                }
+            }
+            "Deconstruct" => self.item_amounts(items, fields, &path, "item_gain", false),
+            "Produce" => {
+                self.item_amounts(items, fields, &path, "item_production", false);
+                if let Some(overflow) = fields.get("overflow") {
+                    self.overflow(&path, overflow);
+                }
+            }
+            "Reduce" => self.upkeep(items, fields, &path),
+            "Convert" => {
+                self.item_amounts(items, fields, &path, "inputs", true);
+                self.item_amounts(items, fields, &path, "outputs", true);
+            }
+            _ => {}
+        }
+
+        if !known_actions.contains(&action_name.as_str()) {
+            self.error(
+                &path,
+                format!(
+                    "unknown action `{action_name}`, expected one of {}",
+                    known_actions.join(", ")
+                ),
+            );
+        }
+    }
+
+    // all fields of an upkeep are optional,
+    // but it has to cost something
+    fn upkeep(&mut self, items: &Map<String, Value>, fields: &Map<String, Value>, path: &str) {
+        if fields.contains_key("item_reduction") {
+            self.item_amounts(items, fields, path, "item_reduction", true);
+        }
+        if fields.contains_key("currency_reduction") {
+            self.amount(fields, path, "currency_reduction", false);
+        }
+        if !fields.contains_key("item_reduction") && !fields.contains_key("currency_reduction") {
+            self.error(
+                path,
+                "expected `item_reduction`, `currency_reduction` or both".to_string(),
+            );
+        }
+
+        if let Some(shortfall) = fields.get("shortfall") {
+            if !shortfall
+                .as_str()
+                .is_some_and(|shortfall| SHORTFALLS.contains(&shortfall))
+            {
+                self.error(
+                    &format!("{path}.shortfall"),
+                    format!("shortfall must be one of {}", SHORTFALLS.join(", ")),
+                );
+            }
+        }
+    }
+
+    // either `"Waste"` or `{"Sell": {"discount": 0.5}}`
+    fn overflow(&mut self, path: &str, overflow: &Value) {
+        let path = format!("{path}.overflow");
+        if overflow.as_str() == Some("Waste") {
+            return;
+        }
+
+        let discount = overflow
+            .get("Sell")
+            .filter(|_| {
+                overflow
+                    .as_object()
+                    .is_some_and(|overflow| overflow.len() == 1)
+            })
+            .and_then(|sell| sell.get("discount"));
+        let Some(discount) = discount else {
+            self.error(
+                &path,
+                "expected \"Waste\" or {\"Sell\": {\"discount\": ...}}".to_string(),
+            );
+            return;
+        };
+
+        if !discount
+            .as_f64()
+            .is_some_and(|discount| (0.0..=1.0).contains(&discount))
+        {
+            self.error(
+                &format!("{path}.Sell.discount"),
+                "expected a number from 0 to 1".to_string(),
+            );
+        }
+    }
+
+    // capacity added per owned unit, keyed by item or category
+    fn storage(
+        &mut self,
+        items: &Map<String, Value>,
+        categories: &[String],
+        path: &str,
+        storage: &Value,
+    ) {
+        let path = format!("{path}.storage");
+        let Some(storage) = storage.as_object() else {
+            self.error(&path, "expected an object of capacities".to_string());
+            return;
+        };
+
+        for (name, capacity) in storage {
+            let capacity_path = format!("{path}.{name}");
+            if !items.contains_key(name) && !categories.contains(name) {
+                self.error(&capacity_path, format!("unknown item or category `{name}`"));
+            }
+            if capacity
+                .as_i64()
+                .filter(|capacity| *capacity >= 0)
+                .is_none()
+            {
+                self.error(
+                    &capacity_path,
+                    "expected a whole number of at least 0".to_string(),
+                );
+            }
+        }
+    }
+
+    // maps of item name to amount, amounts that are
+    // divided by in `max_activate` must be positive
+    fn item_amounts(
+        &mut self,
+        items: &Map<String, Value>,
+        fields: &Map<String, Value>,
+        path: &str,
+        field: &str,
+        must_be_positive: bool,
+    ) {
+        let Some(amounts) = self.field(fields, path, field) else {
+            return;
+        };
+
+        let path = format!("{path}.{field}");
+        let Some(amounts) = amounts.as_object() else {
+            self.error(&path, "expected an object of item amounts".to_string());
+            return;
+        };
+
+        for (item_name, amount) in amounts {
+            let amount_path = format!("{path}.{item_name}");
+
+            if !items.contains_key(item_name) {
+                self.error(&amount_path, format!("unknown item `{item_name}`"));
+            }
+
+            match amount.as_i64() {
+                Some(amount) if must_be_positive && amount <= 0 => {
+                    self.error(&amount_path, format!("must be positive, found {amount}"))
+                }
+                Some(amount) if amount < 0 => {
+                    self.error(&amount_path, format!("cannot be negative, found {amount}"))
+                }
+                Some(_) => {}
+                None if amount.is_u64() => {
+                    self.error(&amount_path, "amount is out of range".to_string())
+                }
+                None => self.error(&amount_path, "expected a whole number".to_string()),
+            }
+        }
+    }
+
+    // numbers kept as `i32`, such as days
+    fn integer(&mut self, object: &Map<String, Value>, path: &str, field: &str, positive: bool) {
+        let out_of_range = object
+            .get(field)
+            .and_then(Value::as_i64)
+            .is_some_and(|value| i32::try_from(value).is_err());
+        if out_of_range {
+            self.error(&join(path, field), "number is out of range".to_string());
+            return;
+        }
+
+        self.amount(object, path, field, positive);
+    }
+
+    // money or a number of items, kept as an `Amount`
+    fn amount(&mut self, object: &Map<String, Value>, path: &str, field: &str, positive: bool) {
+        let Some(value) = self.field(object, path, field) else {
+            return;
+        };
+
+        let path = join(path, field);
+        match value.as_i64() {
+            Some(value) if positive && value <= 0 => {
+                self.error(&path, format!("must be positive, found {value}"))
+            }
+            Some(value) if value < 0 => {
+                self.error(&path, format!("cannot be negative, found {value}"))
+            }
+            Some(_) => {}
+            None if value.is_u64() => self.error(&path, "number is out of range".to_string()),
+            None => self.error(&path, "expected a whole number".to_string()),
+        }
+    }
+
+    fn field<'a>(
+        &mut self,
+        object: &'a Map<String, Value>,
+        path: &str,
+        field: &str,
+    ) -> Option<&'a Value> {
+        let value = object.get(field);
+        if value.is_none() {
+            self.error(path, format!("missing field `{field}`"));
+        }
+
+        return value;
+    }
+}
+
+fn item_industries(items: &Map<String, Value>) -> Vec<&str> {
+    return items
+        .values()
+        .filter_map(|item| item.get("industries")?.as_array())
+        .flatten()
+        .filter_map(Value::as_str)
+        .collect();
+}
+
+fn join(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{path}.{key}")
+    }
+}
+
+#[derive(Default)]
+struct Locations {
+    positions: HashMap<String, (usize, usize)>,
+    duplicates: Vec<(String, (usize, usize))>,
+}
+
+impl Locations {
+    // falls back to the closest parent that was located
+    fn find(&self, path: &str) -> (usize, usize) {
+        let mut path = path;
+
+        loop {
+            if let Some(position) = self.positions.get(path) {
+                return *position;
+            }
+
+            match path.rfind(['.', '[']) {
+                Some(index) => path = &path[..index],
+                None => return self.positions.get("").copied().unwrap_or((1, 1)),
+            }
+        }
+    }
+}
+
+// serde_json does not keep positions in `Value`, so the
+// already valid source is walked once more to record the
+// line and column every path starts at
+fn locate_values(contents: &str) -> Locations {
+    let mut scanner = Scanner {
+        bytes: contents.as_bytes(),
+        index: 0,
+        line: 1,
+        column: 1,
+        locations: Locations::default(),
+    };
+    scanner.value(String::new());
+
+    return scanner.locations;
+}
+
+struct Scanner<'a> {
+    bytes: &'a [u8],
+    index: usize,
+    line: usize,
+    column: usize,
+    locations: Locations,
+}
+
+impl Scanner<'_> {
+    fn peek(&self) -> Option<u8> {
+        return self.bytes.get(self.index).copied();
+    }
+
+    fn bump(&mut self) -> Option<u8> {
+        let byte = self.peek()?;
+        self.index += 1;
+
+        if byte == b'\n' {
+            self.line += 1;
+            self.column = 1;
+        } else if byte & 0xC0 != 0x80 {
+            // continuation bytes of a multibyte
+            // character do not start a new column
+            self.column += 1;
+        }
+
+        return Some(byte);
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|b| b.is_ascii_whitespace()) {
+            self.bump();
+        }
+    }
+
+    fn value(&mut self, path: String) {
+        self.skip_whitespace();
+        self.locations
+            .positions
+            .insert(path.clone(), (self.line, self.column));
+
+        match self.peek() {
+            Some(b'{') => self.object(path),
+            Some(b'[') => self.array(path),
+            Some(b'"') => {
+                self.string();
+            }
+            Some(_) => {
+                while self
+                    .peek()
+                    .is_some_and(|b| !matches!(b, b',' | b'}' | b']') && !b.is_ascii_whitespace())
+                {
+                    self.bump();
+                }
+            }
+            None => {}
+        }
+    }
+
+    fn object(&mut self, path: String) {
+        self.bump();
+
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b'"') => {}
+                Some(b'}') | None => {
+                    self.bump();
+                    return;
+                }
+                Some(_) => {
+                    self.bump();
+                    continue;
+                }
+            }
+
+            let position = (self.line, self.column);
+            let key_path = join(&path, &self.string());
+            if self.locations.positions.contains_key(&key_path) {
+                // serde keeps the last value of a duplicated
+                // key, so positions should point at that one
+                self.locations.positions.retain(|path, _| {
+                    !path
+                        .strip_prefix(key_path.as_str())
+                        .is_some_and(|rest| rest.is_empty() || rest.starts_with(['.', '[']))
+                });
+                self.locations.duplicates.push((key_path.clone(), position));
+            }
+
+            self.skip_whitespace();
+            if self.peek() == Some(b':') {
+                self.bump();
+            }
+            self.value(key_path);
+
+            self.skip_whitespace();
+            if self.peek() == Some(b',') {
+                self.bump();
+            }
+        }
+    }
+
+    fn array(&mut self, path: String) {
+        self.bump();
+
+        let mut index = 0;
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b']') | None => {
+                    self.bump();
+                    return;
+                }
+                Some(b',') => {
+                    self.bump();
+                }
+                Some(_) => {
+                    self.value(format!("{path}[{index}]"));
+                    index += 1;
+                }
+            }
+        }
+    }
+
+    fn string(&mut self) -> String {
+        self.bump();
+
+        let start = self.index;
+        let mut escaped = false;
+        while let Some(byte) = self.bump() {
+            match byte {
+                b'\\' if !escaped => escaped = true,
+                b'"' if !escaped => break,
+                _ => escaped = false,
+            }
+        }
+
+        let raw = &self.bytes[start..self.index.saturating_sub(1).max(start)];
+        let raw = String::from_utf8_lossy(raw);
+
+        // let serde deal with escape sequences
+        return serde_json::from_str(&format!("\"{raw}\"")).unwrap_or(raw.into_owned());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hundred_days::game::DEFAULT_SCENARIO;
+
+    fn errors(contents: &str) -> Vec<ValidationError> {
+        return match validate_scenario("test.json", contents) {
+            Ok(_) => Vec::new(),
+            Err(report) => report.errors,
+        };
+    }
+
+    // path, line and column of every error
+    fn locations(contents: &str) -> Vec<(String, usize, usize)> {
+        return errors(contents)
+            .into_iter()
+            .map(|error| (error.path, error.line, error.column))
+            .collect();
+    }
+
+    fn location(path: &str, line: usize, column: usize) -> (String, usize, usize) {
+        return (path.to_string(), line, column);
+    }
+
+    #[test]
+    fn the_default_scenario_is_valid() {
+        assert!(validate_scenario("hundred_days.json", DEFAULT_SCENARIO).is_ok());
+    }
+
+    #[test]
+    fn syntax_errors_point_at_the_broken_json() {
+        let errors = errors("{\n  \"day\": 10,\n  \"currency\": ?\n}");
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!((errors[0].line, errors[0].column), (3, 15));
+        assert!(errors[0].path.is_empty());
+    }
+
+    #[test]
+    fn every_problem_is_reported_where_it_is() {
+        let scenario = r#"{
+  "day": "ten",
+  "currency": -5,
+  "items": {
+    "Wood": { "name": "Wood", "amount": 0, "category": "Resource",
+      "actions_active": [{ "Buy": {} }], "actions_passive": [] }
+  },
+  "market": { "Gold": { "slippage": 0.1 } }
+}"#;
+
+        assert_eq!(
+            locations(scenario),
+            [
+                location("day", 2, 10),
+                location("currency", 3, 15),
+                location("items.Wood.actions_active[0].Buy", 6, 35),
+                location("market.Gold", 8, 23),
+            ]
+        );
+    }
+
+    #[test]
+    fn duplicate_keys_point_at_the_second_one() {
+        let scenario = r#"{
+  "day": 10,
+  "day": 20,
+  "currency": 0,
+  "items": {}
+}"#;
+
+        let errors = errors(scenario);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].message, "duplicate key");
+        assert_eq!((errors[0].line, errors[0].column), (3, 3));
+    }
+
+    #[test]
+    fn missing_fields_are_reported_on_their_parent() {
+        assert_eq!(
+            locations("{\n  \"day\": 10,\n  \"items\": {}\n}"),
+            [location("", 1, 1)]
+        );
+        assert_eq!(
+            errors("{ \"day\": 10, \"items\": {} }")[0].message,
+            "missing field `currency`"
+        );
+    }
+
+    #[test]
+    fn problems_only_serde_finds_keep_their_position() {
+        let scenario = r#"{
+  "day": 10,
+  "currency": 0,
+  "items": {},
+  "events": [
+    { "name": "Rain", "description": 5, "probability": 0.5, "effects": [] }
+  ]
+}"#;
+
+        let errors = errors(scenario);
+        assert_eq!(errors.len(), 1);
+        assert_eq!((errors[0].line, errors[0].column), (6, 38));
+    }
+
+    fn market_errors(market: &str) -> Vec<String> {
+        let scenario = format!(
+            r#"{{
+                "day": 10,
+                "currency": 0,
+                "items": {{
+                    "Gems": {{ "name": "Gems", "amount": 0, "category": "Resource",
+                        "actions_active": [], "actions_passive": [] }}
+                }},
+                "market": {{ "Gems": {market} }}
+            }}"#
+        );
+
+        return errors(&scenario)
+            .into_iter()
+            .map(|error| error.path)
+            .collect();
+    }
+
+    #[test]
+    fn market_bounds_are_checked_against_their_defaults() {
+        assert!(market_errors(r#"{ "floor": 0.5, "ceiling": 2.0 }"#).is_empty());
+        // the default ceiling is 4
+        assert_eq!(market_errors(r#"{ "floor": 5.0 }"#), ["market.Gems.floor"]);
+        // the default floor is 0.25
+        assert_eq!(
+            market_errors(r#"{ "ceiling": 0.1 }"#),
+            ["market.Gems.ceiling"]
+        );
+        assert_eq!(market_errors(r#"{ "floor": 0 }"#), ["market.Gems.floor"]);
+    }
+
+    #[test]
+    fn slippage_is_zero_or_large_enough_to_move_the_price() {
+        assert!(market_errors(r#"{ "slippage": 0 }"#).is_empty());
+        assert!(market_errors(r#"{ "slippage": 0.001 }"#).is_empty());
+        assert_eq!(
+            market_errors(r#"{ "slippage": 1e-9 }"#),
+            ["market.Gems.slippage"]
+        );
+        assert_eq!(
+            market_errors(r#"{ "slippage": 1.0 }"#),
+            ["market.Gems.slippage"]
+        );
+    }
+}
@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use super::{action::active::Active, amount::Amount, game::GameState, market};
+
+// what an item is worth if everything
+// owned was turned into currency now
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ItemValue {
+    pub name: String,
+    pub amount: Amount,
+    pub value: Amount,
+    // false for items that can neither be
+    // sold nor deconstructed into something sold
+    pub sellable: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Valuation {
+    pub currency: Amount,
+    // sorted by item name
+    pub items: Vec<ItemValue>,
+}
+
+impl Valuation {
+    // only shown and compared, so a total
+    // too large to keep saturates
+    pub fn total(&self) -> Amount {
+        let items = self.items.iter().map(|item| item.value).sum::<Amount>();
+        return self.currency.saturating_add(items);
+    }
+
+    pub fn item(&self, name: &str) -> Option<&ItemValue> {
+        return self.items.iter().find(|item| item.name == name);
+    }
+}
+
+pub fn sell_price(game: &GameState, name: &str) -> Option<Amount> {
+    return game
+        .items
+        .get(name)?
+        .actions_active
+        .iter()
+        .find_map(|action| match action {
+            Active::Sell { sell_price } => Some(*sell_price),
+            _ => None,
+        });
+}
+
+fn item_gain<'a>(game: &'a GameState, name: &str) -> Option<&'a HashMap<String, Amount>> {
+    return game
+        .items
+        .get(name)?
+        .actions_active
+        .iter()
+        .find_map(|action| match action {
+            Active::Deconstruct { item_gain } => Some(item_gain),
+            _ => None,
+        });
+}
+
+// liquidation value of everything owned, selling one unit after
+// another so slippage is included. Items with a sell price are
+// sold first, in name order, then items without one are
+// deconstructed and what they give is sold on top
+pub fn valuate(game: &GameState) -> Valuation {
+    // trades move the prices in this copy only
+    let mut market_game = game.clone();
+
+    let mut names = game.items.keys().cloned().collect::<Vec<String>>();
+    names.sort();
+
+    let mut sell = |name: &str, amount: Amount| -> Option<Amount> {
+        let price = sell_price(game, name)?;
+        let amount = amount.count();
+        let value = market::quote(&market_game, name, price, amount, false).unwrap_or(Amount::MAX);
+        market::record_trade(&mut market_game, name, price, amount, false);
+
+        return Some(value);
+    };
+
+    let mut items = Vec::new();
+    for name in &names {
+        let amount = game.items[name].amount.max(Amount::ZERO);
+        let value = if amount.is_positive() {
+            sell(name, amount)
+        } else {
+            None
+        };
+
+        items.push(ItemValue {
+            name: name.clone(),
+            amount,
+            value: value.unwrap_or(Amount::ZERO),
+            sellable: sell_price(game, name).is_some(),
+        });
+    }
+
+    for item in items.iter_mut().filter(|item| !item.sellable) {
+        let Some(item_gain) = item_gain(game, &item.name) else {
+            continue;
+        };
+
+        let mut gains = item_gain.iter().collect::<Vec<(&String, &Amount)>>();
+        gains.sort();
+
+        for (gain, per_unit) in gains {
+            let gained = per_unit
+                .checked_mul(item.amount.count())
+                .unwrap_or(Amount::MAX);
+            if let Some(value) = sell(gain, gained) {
+                item.value = item.value.saturating_add(value);
+                item.sellable = true;
+            }
+        }
+    }
+
+    return Valuation {
+        currency: game.currency,
+        items,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(market: &str) -> GameState {
+        let scenario = format!(
+            r#"{{
+                "day": 10,
+                "currency": 5,
+                "items": {{
+                    "Wood": {{ "name": "Wood", "amount": 10, "category": "Resource",
+                        "actions_active": [{{ "Sell": {{ "sell_price": 2 }} }}],
+                        "actions_passive": [] }},
+                    "House": {{ "name": "House", "amount": 2, "category": "Building",
+                        "actions_active": [{{ "Deconstruct": {{ "item_gain": {{ "Wood": 3 }} }} }}],
+                        "actions_passive": [] }},
+                    "Relic": {{ "name": "Relic", "amount": 1, "category": "Resource",
+                        "actions_active": [], "actions_passive": [] }}
+                }},
+                "market": {market}
+            }}"#
+        );
+
+        return GameState::generate_from_json("test.json", &scenario).unwrap();
+    }
+
+    #[test]
+    fn items_are_sold_or_deconstructed_and_sold() {
+        let valuation = valuate(&game("{}"));
+
+        let names = valuation
+            .items
+            .iter()
+            .map(|item| item.name.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(names, ["House", "Relic", "Wood"]);
+
+        let value = |name: &str| {
+            let item = valuation.item(name).unwrap();
+            (item.value, item.sellable)
+        };
+        assert_eq!(value("Wood"), (Amount::new(20), true));
+        assert_eq!(value("House"), (Amount::new(12), true));
+        assert_eq!(value("Relic"), (Amount::ZERO, false));
+        assert_eq!(valuation.total(), Amount::new(37));
+    }
+
+    #[test]
+    fn slippage_lowers_the_value_without_moving_the_market() {
+        let game = game(r#"{ "Wood": { "slippage": 0.1 } }"#);
+        let valuation = valuate(&game);
+
+        // Wood is sold first, lowering what the House's Wood is worth
+        let wood = valuation.item("Wood").unwrap().value;
+        let house = valuation.item("House").unwrap().value;
+        assert!(wood < Amount::new(20));
+        assert!(house < Amount::new(12));
+
+        assert_eq!(game.market["Wood"].multiplier, 1.0);
+        assert_eq!(valuate(&game), valuation);
+    }
+}
@@ -0,0 +1 @@
+pub mod hundred_days;
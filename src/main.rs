@@ -1,12 +1,67 @@
 mod app;
+mod cli;
 mod crossterm;
-mod hundred_days;
+mod save;
+mod scenarios;
+mod script;
 mod ui;
 
-use crate::crossterm::run;
-use std::error::Error;
+use crate::{
+    cli::{Args, USAGE},
+    crossterm::run,
+    script::run_script,
+};
+use std::{error::Error, process};
+use terminal_hundred_days::hundred_days;
 
 fn main() -> Result<(), Box<dyn Error>> {
-    run()?;
+    let args = match Args::from_env() {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("{err}\n\n{USAGE}");
+            process::exit(2);
+        }
+    };
+
+    if args.help {
+        println!("{USAGE}");
+        return Ok(());
+    }
+
+    let result = match &args.script {
+        _ if args.high_scores => print_high_scores(),
+        Some(path) => run_script(path, &args),
+        None => run(args),
+    };
+
+    if let Err(err) = result {
+        eprintln!("{err}");
+        process::exit(1);
+    }
+
     Ok(())
-}
\ No newline at end of file
+}
+
+fn print_high_scores() -> Result<(), Box<dyn Error>> {
+    let high_scores =
+        save::read_high_scores().map_err(|err| format!("Could not read high scores: {err}"))?;
+
+    if high_scores.tables.is_empty() {
+        println!("No high scores yet");
+    }
+
+    for table in high_scores.tables.values() {
+        println!("{} (seed {})", table.scenario, table.seed);
+        for (place, score) in table.scores.iter().enumerate() {
+            println!(
+                "  {:>2}. {:<20} {:>8}  {}",
+                place + 1,
+                score.name,
+                score.net_worth,
+                score.date
+            );
+        }
+    }
+
+    return Ok(());
+}
@@ -0,0 +1,159 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app::HistoryItem,
+    hundred_days::{
+        game::{GameState, StopOn},
+        high_scores::{date_from_unix, HighScores},
+        report::Report,
+    },
+};
+
+const SAVE_DIRECTORY: &str = "saves";
+const HIGH_SCORES_FILE: &str = "high_scores.json";
+const REPORT_DIRECTORY: &str = "reports";
+pub const MANUAL_SLOT_COUNT: u8 = 3;
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum SaveSlot {
+    // written when the game is exited and
+    // offered as "continue" on the next start
+    Auto,
+    Manual(u8),
+}
+
+impl SaveSlot {
+    pub fn file_name(&self) -> String {
+        match self {
+            SaveSlot::Auto => "autosave.json".to_string(),
+            SaveSlot::Manual(slot) => format!("slot_{slot}.json"),
+        }
+    }
+
+    pub fn path(&self) -> PathBuf {
+        Path::new(SAVE_DIRECTORY).join(self.file_name())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SaveData {
+    pub game_state: GameState,
+    pub history: Vec<HistoryItem>,
+    pub activation_amount: i32,
+    #[serde(default)]
+    pub stop_on: StopOn,
+}
+
+pub fn exists(slot: SaveSlot) -> bool {
+    return slot.path().is_file();
+}
+
+pub fn write(slot: SaveSlot, data: &SaveData) -> io::Result<()> {
+    fs::create_dir_all(SAVE_DIRECTORY)?;
+
+    let contents = serde_json::to_string_pretty(data)?;
+    fs::write(slot.path(), contents)?;
+
+    return Ok(());
+}
+
+pub fn read(slot: SaveSlot) -> io::Result<SaveData> {
+    return read_path(&slot.path());
+}
+
+pub fn read_path(path: &Path) -> io::Result<SaveData> {
+    let contents = fs::read_to_string(path)?;
+    let data: SaveData = serde_json::from_str(&contents)?;
+
+    return Ok(data);
+}
+
+pub fn remove(slot: SaveSlot) -> io::Result<()> {
+    if !exists(slot) {
+        return Ok(());
+    }
+
+    return fs::remove_file(slot.path());
+}
+
+// empty until the first game is finished
+pub fn read_high_scores() -> io::Result<HighScores> {
+    let contents = match fs::read_to_string(Path::new(SAVE_DIRECTORY).join(HIGH_SCORES_FILE)) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(HighScores::default()),
+        Err(err) => return Err(err),
+    };
+
+    let mut high_scores: HighScores = serde_json::from_str(&contents)?;
+    high_scores.migrate();
+    return Ok(high_scores);
+}
+
+pub fn write_high_scores(high_scores: &HighScores) -> io::Result<()> {
+    fs::create_dir_all(SAVE_DIRECTORY)?;
+
+    let contents = serde_json::to_string_pretty(high_scores)?;
+    fs::write(Path::new(SAVE_DIRECTORY).join(HIGH_SCORES_FILE), contents)?;
+
+    return Ok(());
+}
+
+pub fn today() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs());
+
+    return date_from_unix(seconds);
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum ReportFormat {
+    Json,
+    Markdown,
+}
+
+impl ReportFormat {
+    // markdown for `.md` files, json for anything else
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("md") => ReportFormat::Markdown,
+            _ => ReportFormat::Json,
+        }
+    }
+
+    pub fn extension(&self) -> &str {
+        match self {
+            ReportFormat::Json => "json",
+            ReportFormat::Markdown => "md",
+        }
+    }
+}
+
+pub fn write_report(path: &Path, report: &Report, format: ReportFormat) -> io::Result<()> {
+    let contents = match format {
+        ReportFormat::Json => report.to_json(),
+        ReportFormat::Markdown => report.to_markdown(),
+    };
+
+    return fs::write(path, contents);
+}
+
+// writes the report to a new file in
+// the reports directory, named by time
+pub fn export_report(report: &Report, format: ReportFormat) -> io::Result<PathBuf> {
+    fs::create_dir_all(REPORT_DIRECTORY)?;
+
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs());
+    let path = Path::new(REPORT_DIRECTORY).join(format!("report_{seconds}.{}", format.extension()));
+    write_report(&path, report, format)?;
+
+    return Ok(path);
+}
@@ -0,0 +1,58 @@
+use std::{fs, path::PathBuf};
+
+use crate::hundred_days::game::GameState;
+
+const SCENARIO_DIRECTORY: &str = "scenarios";
+
+// a scenario offered by the scenario picker
+pub struct ScenarioEntry {
+    pub name: String,
+    // none for the built in scenario
+    pub path: Option<PathBuf>,
+    // invalid scenarios are still listed,
+    // with what is wrong with them
+    pub game: Result<GameState, String>,
+}
+
+// the built in scenario followed by every json file
+// in the scenario directory, sorted by file name
+pub fn list() -> Vec<ScenarioEntry> {
+    let default = GameState::generate_default();
+    let mut entries = vec![ScenarioEntry {
+        name: format!("{} (built in)", default.title),
+        path: None,
+        game: Ok(default),
+    }];
+
+    let Ok(directory) = fs::read_dir(SCENARIO_DIRECTORY) else {
+        return entries;
+    };
+
+    let mut paths = directory
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        })
+        .collect::<Vec<PathBuf>>();
+    paths.sort();
+
+    for path in paths {
+        let game = GameState::generate_from_file(&path).map_err(|err| err.to_string());
+        let name = match &game {
+            Ok(game) if !game.title.is_empty() => game.title.clone(),
+            _ => path
+                .file_name()
+                .map_or(String::new(), |name| name.to_string_lossy().to_string()),
+        };
+
+        entries.push(ScenarioEntry {
+            name,
+            game,
+            path: Some(path),
+        });
+    }
+
+    return entries;
+}
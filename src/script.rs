@@ -0,0 +1,65 @@
+use std::{error::Error, fs, path::Path};
+
+use crate::{
+    cli::Args,
+    hundred_days::{
+        engine::{Command, Engine},
+        report::Report,
+    },
+    save::{self, ReportFormat},
+};
+
+// plays a game without a terminal, printing
+// every event the commands in the script cause.
+// Games continued from a save go on where they
+// were saved
+pub fn run_script(path: &Path, args: &Args) -> Result<(), Box<dyn Error>> {
+    let contents = fs::read_to_string(path)
+        .map_err(|err| format!("Could not read script {}: {err}", path.display()))?;
+
+    let game = match &args.save {
+        Some(save_path) => {
+            save::read_path(save_path)
+                .map_err(|err| format!("Could not load save {}: {err}", save_path.display()))?
+                .game_state
+        }
+        None => args.load_scenario()?,
+    };
+    let mut engine = Engine::new(game);
+
+    for (line_number, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let command: Command = line
+            .parse()
+            .map_err(|err| format!("{}:{}: {err}", path.display(), line_number + 1))?;
+
+        for event in engine.execute(command) {
+            println!("{event}");
+        }
+    }
+
+    println!(
+        "day {}, currency {}, net worth {}",
+        engine.state.day,
+        engine.state.currency,
+        engine.state.net_worth()
+    );
+    if engine.state.objective.has_target() {
+        println!(
+            "objective: {}, {}",
+            engine.state.objective, engine.state.objective_status
+        );
+    }
+
+    if let Some(report_path) = &args.report {
+        let report = Report::new(&engine.state, None);
+        save::write_report(report_path, &report, ReportFormat::from_path(report_path))
+            .map_err(|err| format!("Could not write report {}: {err}", report_path.display()))?;
+    }
+
+    return Ok(());
+}
@@ -2,15 +2,27 @@ use ratatui::widgets::ListState;
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
+    symbols::Marker,
     text::{Line, Span},
-    widgets::{Block, BorderType, Borders, List, ListItem, Paragraph, Tabs, Wrap},
+    widgets::{
+        Axis, Block, BorderType, Borders, Chart, Dataset, GraphType, List, ListItem, Paragraph,
+        Tabs, Wrap,
+    },
     Frame,
 };
 
-use crate::hundred_days::item::ItemCategory;
 use crate::{
-    app::{App, Table},
-    hundred_days::action::Action,
+    app::{App, MenuEntry, Screen, Tab, Table, MENU_ENTRIES},
+    hundred_days::{
+        action::Action,
+        amount::Amount,
+        construction,
+        objective::{Objective, ObjectiveStatus},
+        report::{DayResult, Report},
+        research::{self, TechStatus},
+        storage,
+        valuation::{valuate, Valuation},
+    },
 };
 
 const DEFAULT_STYLE: Style = Style {
@@ -29,8 +41,30 @@ const HIGHLIGHT_STYLE: Style = Style {
     underline_color: None,
 };
 
+// lines of a chart take these in turn
+const CHART_COLORS: [Color; 8] = [
+    Color::Rgb(255, 105, 180),
+    Color::Cyan,
+    Color::Yellow,
+    Color::Green,
+    Color::Magenta,
+    Color::Blue,
+    Color::LightRed,
+    Color::White,
+];
+
+const ERROR_STYLE: Style = Style {
+    fg: Some(Color::Red),
+    bg: None,
+    add_modifier: Modifier::empty(),
+    sub_modifier: Modifier::empty(),
+    underline_color: None,
+};
+
 pub fn draw(f: &mut Frame, app: &mut App) {
-    if app.game_state.day < 0 {
+    if app.screen != Screen::Game {
+        draw_menu_screen(f, app);
+    } else if app.engine.state.day < 0 {
         draw_end_screen(f, app);
     } else {
         draw_game_screen(f, app);
@@ -44,22 +78,320 @@ pub fn draw_end_screen(f: &mut Frame, app: &mut App) {
     let block = Block::default()
         .style(DEFAULT_STYLE)
         .borders(Borders::ALL)
-        .title(format!(" In {} days ", app.game_state.day))
+        .title(format!(" In {} days ", app.engine.state.day))
         .title_alignment(Alignment::Center)
         .border_type(BorderType::Plain);
     f.render_widget(block, size);
 
-    draw_game_ended_stats(f, app, size);
+    // games loaded after they ended have no report
+    // yet, those are not compared to the personal best
+    let report = app
+        .report
+        .clone()
+        .unwrap_or_else(|| Report::new(&app.engine.state, None));
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Length(9),
+            Constraint::Percentage(50),
+            Constraint::Min(0),
+        ])
+        .split(size);
+
+    let tables = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage(30),
+            Constraint::Percentage(40),
+            Constraint::Percentage(30),
+        ])
+        .split(rows[1]);
+
+    draw_game_ended_stats(f, app, &report, rows[0]);
+    draw_report_worth(f, &report, tables[0]);
+    draw_report_trades(f, &report, tables[1]);
+    draw_high_scores(f, app, tables[2]);
+    draw_money_chart(f, app, rows[2]);
+}
+
+pub fn draw_menu_screen(f: &mut Frame, app: &App) {
+    let size = f.size();
+
+    let block = Block::default()
+        .style(DEFAULT_STYLE)
+        .borders(Borders::ALL)
+        .title(" Hundred Days ")
+        .title_alignment(Alignment::Center)
+        .border_type(BorderType::Plain);
+    f.render_widget(block, size);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(size);
+
+    let hint = match app.screen {
+        Screen::Menu => "Enter to choose, q to quit",
+        Screen::ScenarioPicker => "Enter to start, Backspace to go back",
+        _ => "Backspace to go back",
+    };
+    f.render_widget(Paragraph::new(hint).alignment(Alignment::Center), rows[1]);
+
+    match app.screen {
+        Screen::ScenarioPicker => draw_scenario_picker(f, app, rows[0]),
+        Screen::HighScores => draw_high_score_tables(f, app, rows[0]),
+        _ => draw_menu(f, app, rows[0]),
+    }
+}
+
+fn draw_menu(f: &mut Frame, app: &App, area: Rect) {
+    let column = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage(35),
+            Constraint::Percentage(30),
+            Constraint::Percentage(35),
+        ])
+        .split(area)[1];
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage(30),
+            Constraint::Length(MENU_ENTRIES.len() as u16 + 2),
+            Constraint::Min(0),
+        ])
+        .split(column);
+
+    let entries = MENU_ENTRIES
+        .iter()
+        .map(|entry| {
+            let line = ListItem::new(entry.label());
+            if *entry == MenuEntry::Continue && !app.autosave_exists {
+                line.style(Style::default().fg(Color::DarkGray))
+            } else {
+                line
+            }
+        })
+        .collect::<Vec<ListItem>>();
+
+    let menu = List::new(entries)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(HIGHLIGHT_STYLE)
+                .border_type(BorderType::Plain),
+        )
+        .highlight_style(HIGHLIGHT_STYLE)
+        .highlight_symbol("> ");
+
+    f.render_stateful_widget(
+        menu,
+        rows[1],
+        &mut ListState::default().with_selected(Some(app.menu_index)),
+    );
+
+    if let Some(message) = &app.menu_message {
+        let message = Paragraph::new(message.as_str())
+            .style(ERROR_STYLE)
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true });
+        f.render_widget(message, rows[2]);
+    }
+}
+
+// scenarios on the left, the selected one on the right
+fn draw_scenario_picker(f: &mut Frame, app: &App, area: Rect) {
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+        .split(area);
+
+    let scenarios = app
+        .scenarios
+        .iter()
+        .map(|entry| {
+            let line = ListItem::new(entry.name.as_str());
+            if entry.game.is_err() {
+                line.style(ERROR_STYLE)
+            } else {
+                line
+            }
+        })
+        .collect::<Vec<ListItem>>();
+
+    let scenarios = List::new(scenarios)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(HIGHLIGHT_STYLE)
+                .title(" Scenarios ")
+                .title_alignment(Alignment::Center)
+                .border_type(BorderType::Plain),
+        )
+        .highlight_style(HIGHLIGHT_STYLE)
+        .highlight_symbol("> ");
+
+    f.render_stateful_widget(
+        scenarios,
+        columns[0],
+        &mut ListState::default().with_selected(Some(app.scenario_index)),
+    );
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(DEFAULT_STYLE)
+        .title(" Scenario ")
+        .title_alignment(Alignment::Center)
+        .border_type(BorderType::Plain);
+
+    let Some(entry) = app.scenarios.get(app.scenario_index) else {
+        f.render_widget(block, columns[1]);
+        return;
+    };
+
+    let source = entry
+        .path
+        .as_ref()
+        .map_or("Built in".to_string(), |path| path.display().to_string());
+
+    let details = match &entry.game {
+        Ok(game) => {
+            let mut lines = Vec::new();
+            if !game.author.is_empty() {
+                lines.push(Line::from(format!("By: {}", game.author)));
+            }
+            lines.extend([
+                Line::from(format!("Goal: {}", game.objective)),
+                Line::from(format!("Length: {} days", game.day)),
+                Line::from(format!("Difficulty: {}", game.difficulty)),
+                Line::from(format!("Starting money: {}", game.currency)),
+                Line::from(format!("File: {source}")),
+                Line::from(""),
+                Line::from(game.description.as_str()),
+            ]);
+            Paragraph::new(lines)
+        }
+        Err(err) => Paragraph::new(vec![
+            Line::from(format!("File: {source}")),
+            Line::from(""),
+            Line::from(Span::styled("This scenario cannot be played:", ERROR_STYLE)),
+            Line::from(Span::styled(err.as_str(), ERROR_STYLE)),
+        ]),
+    };
+
+    f.render_widget(details.block(block).wrap(Wrap { trim: false }), columns[1]);
+}
+
+// tables of every scenario and seed on the left,
+// the scores of the selected one on the right
+fn draw_high_score_tables(f: &mut Frame, app: &App, area: Rect) {
+    let high_scores = match &app.high_scores {
+        Ok(high_scores) if !high_scores.tables.is_empty() => high_scores,
+        result => {
+            let text = match result {
+                Err(err) => {
+                    Paragraph::new(format!("Could not read high scores: {err}")).style(ERROR_STYLE)
+                }
+                Ok(_) => Paragraph::new("No high scores yet"),
+            };
+            f.render_widget(
+                text.alignment(Alignment::Center).block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title(" High Scores ")
+                        .title_alignment(Alignment::Center)
+                        .border_type(BorderType::Plain),
+                ),
+                area,
+            );
+            return;
+        }
+    };
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+        .split(area);
+
+    let tables = high_scores
+        .tables
+        .values()
+        .map(|table| ListItem::new(format!("{} (seed {})", table.scenario, table.seed)))
+        .collect::<Vec<ListItem>>();
+
+    let tables = List::new(tables)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(HIGHLIGHT_STYLE)
+                .title(" Scenarios ")
+                .title_alignment(Alignment::Center)
+                .border_type(BorderType::Plain),
+        )
+        .highlight_style(HIGHLIGHT_STYLE)
+        .highlight_symbol("> ");
+
+    f.render_stateful_widget(
+        tables,
+        columns[0],
+        &mut ListState::default().with_selected(Some(app.high_score_index)),
+    );
+
+    let scores = high_scores
+        .tables
+        .values()
+        .nth(app.high_score_index)
+        .map_or(Vec::new(), |table| {
+            table
+                .scores
+                .iter()
+                .enumerate()
+                .map(|(place, score)| {
+                    ListItem::new(format!(
+                        "{:>2}. {} {} ({})",
+                        place + 1,
+                        score.name,
+                        score.net_worth,
+                        score.date
+                    ))
+                })
+                .collect()
+        });
+
+    let scores = List::new(scores).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(DEFAULT_STYLE)
+            .title(" High Scores ")
+            .title_alignment(Alignment::Center)
+            .border_type(BorderType::Plain),
+    );
+    f.render_widget(scores, columns[1]);
 }
 
 pub fn draw_game_screen(f: &mut Frame, app: &mut App) {
     let size = f.size();
 
+    let stop_on = match (app.stop_on.event, app.stop_on.depleted) {
+        (false, false) => String::new(),
+        (true, false) => "- stops on events ".to_string(),
+        (false, true) => "- stops when anything runs out ".to_string(),
+        (true, true) => "- stops on events or when anything runs out ".to_string(),
+    };
+
     // Surrounding Block
     let block = Block::default()
         .style(DEFAULT_STYLE)
         .borders(Borders::ALL)
-        .title(format!(" In {} days ", app.game_state.day))
+        .title(format!(
+            " In {} days {stop_on}{}- seed {} ",
+            app.engine.state.day,
+            objective_title(app),
+            app.engine.state.seed
+        ))
         .title_alignment(Alignment::Center)
         .border_type(BorderType::Plain);
     f.render_widget(block, size);
@@ -80,42 +412,246 @@ pub fn draw_game_screen(f: &mut Frame, app: &mut App) {
         ])
         .split(columns[0]);
 
-    let cash_char_count = (app.game_state.currency.checked_ilog10().unwrap_or(0) + 6) as u16;
+    let valuation = valuate(&app.engine.state);
+    let cash = format!("$ {} | Net worth {}", valuation.currency, valuation.total());
     let top_row = Layout::default()
         .direction(Direction::Horizontal)
-        .constraints([Constraint::default(), Constraint::Min(cash_char_count)])
+        .constraints([
+            Constraint::default(),
+            Constraint::Min(cash.chars().count() as u16 + 2),
+        ])
         .split(rows[0]);
 
+    // a panel per item category, side by side
+    let table_count = app.tables.len().max(1) as u32;
     let middle_row = Layout::default()
         .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .constraints(
+            app.tables
+                .iter()
+                .map(|_| Constraint::Ratio(1, table_count))
+                .collect::<Vec<Constraint>>(),
+        )
         .split(rows[1]);
 
     draw_tabs(f, app, top_row[0]);
-    draw_cash(f, app, top_row[1]);
-
-    draw_resources(f, app, middle_row[0]);
-    draw_buildings(f, app, middle_row[1]);
+    draw_cash(f, &cash, top_row[1]);
 
     draw_history(f, app, rows[2]);
 
-    draw_actions(f, app, columns[1]);
+    match app.tab {
+        Tab::MainGame => {
+            for index in 0..app.tables.len() {
+                draw_item_table(f, app, index, middle_row[index]);
+            }
+
+            draw_actions(f, app, &valuation, columns[1]);
+        }
+        Tab::NightMarket => {
+            draw_night_market(f, app, rows[1]);
+            draw_offer(f, app, columns[1]);
+        }
+        Tab::Research => {
+            draw_techs(f, app, rows[1]);
+            draw_tech(f, app, columns[1]);
+        }
+        Tab::Progress => {
+            draw_money_chart(f, app, rows[1]);
+            draw_item_chart(f, app, columns[1]);
+        }
+    }
+}
+
+// the objective while it is not decided yet, and
+// whether it was won or lost once it is
+fn objective_title(app: &App) -> String {
+    let state = &app.engine.state;
+    return match state.objective_status {
+        _ if state.objective == Objective::MaximizeNetWorth => String::new(),
+        ObjectiveStatus::Pending => format!("- goal: {} ", state.objective),
+        status => format!("- goal {status} "),
+    };
+}
+
+// one line per series across every day played so far, the
+// x axis counts days played so the chart grows to the right
+fn draw_chart(
+    f: &mut Frame,
+    app: &App,
+    title: &str,
+    series: Vec<(String, Vec<Amount>)>,
+    area: Rect,
+) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(DEFAULT_STYLE)
+        .title(format!(" {title} "))
+        .title_alignment(Alignment::Center)
+        .border_type(BorderType::Plain);
+
+    let snapshots = &app.engine.state.snapshots;
+    let Some(first) = snapshots.first() else {
+        let empty = Paragraph::new("Pass a day to start charting")
+            .alignment(Alignment::Center)
+            .block(block);
+        f.render_widget(empty, area);
+        return;
+    };
+
+    let points = series
+        .iter()
+        .map(|(_, values)| {
+            values
+                .iter()
+                .enumerate()
+                .map(|(i, value)| (i as f64, value.as_f64()))
+                .collect::<Vec<(f64, f64)>>()
+        })
+        .collect::<Vec<Vec<(f64, f64)>>>();
+
+    let datasets = series
+        .iter()
+        .zip(&points)
+        .enumerate()
+        .map(|(i, ((name, _), points))| {
+            Dataset::default()
+                .name(name.clone())
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(CHART_COLORS[i % CHART_COLORS.len()]))
+                .data(points)
+        })
+        .collect::<Vec<Dataset>>();
+
+    let values = series.iter().flat_map(|(_, values)| values.iter());
+    let lowest = values
+        .clone()
+        .copied()
+        .min()
+        .unwrap_or(Amount::ZERO)
+        .min(Amount::ZERO);
+    let highest = values
+        .copied()
+        .max()
+        .unwrap_or(Amount::ZERO)
+        .max(Amount::new(1));
+    let last_day = snapshots.last().map_or(first.day, |snapshot| snapshot.day);
+    let played = (first.day - last_day).max(1);
+
+    let chart = Chart::new(datasets)
+        .block(block)
+        .x_axis(Axis::default().bounds([0.0, played as f64]).labels(vec![
+            Span::raw(format!("Day {}", first.day)),
+            Span::raw(format!("Day {last_day}")),
+        ]))
+        .y_axis(
+            Axis::default()
+                .bounds([lowest.as_f64(), highest.as_f64()])
+                .labels(vec![
+                    Span::raw(lowest.to_string()),
+                    Span::raw(highest.to_string()),
+                ]),
+        );
+
+    f.render_widget(chart, area);
+}
+
+fn draw_money_chart(f: &mut Frame, app: &App, area: Rect) {
+    let snapshots = &app.engine.state.snapshots;
+    let series = vec![
+        (
+            "Net worth".to_string(),
+            snapshots
+                .iter()
+                .map(|snapshot| snapshot.net_worth)
+                .collect(),
+        ),
+        (
+            "Money".to_string(),
+            snapshots.iter().map(|snapshot| snapshot.currency).collect(),
+        ),
+    ];
+
+    draw_chart(f, app, "Progress", series, area);
+}
+
+// every item that was owned at some point
+fn draw_item_chart(f: &mut Frame, app: &App, area: Rect) {
+    let snapshots = &app.engine.state.snapshots;
+    let mut names = snapshots
+        .iter()
+        .flat_map(|snapshot| snapshot.items.iter())
+        .filter(|(_, amount)| amount.is_positive())
+        .map(|(name, _)| name.clone())
+        .collect::<Vec<String>>();
+    names.sort();
+    names.dedup();
+
+    let series = names
+        .into_iter()
+        .map(|name| {
+            let amounts = snapshots
+                .iter()
+                .map(|snapshot| snapshot.items.get(&name).copied().unwrap_or_default())
+                .collect();
+            (name, amounts)
+        })
+        .collect();
+
+    draw_chart(f, app, "Items", series, area);
 }
 
-fn draw_game_ended_stats(f: &mut Frame, app: &App, area: Rect) {
+fn draw_game_ended_stats(f: &mut Frame, app: &App, report: &Report, area: Rect) {
     let block = Block::default()
         .style(DEFAULT_STYLE)
         .borders(Borders::ALL)
         .title_alignment(Alignment::Center)
         .border_type(BorderType::Plain);
 
+    let personal_best = match report.personal_best {
+        Some(_) if report.is_personal_best() => "A new personal best!".to_string(),
+        Some(best) => format!("Your personal best is {best}"),
+        None => String::new(),
+    };
+    let day = |label: &str, result: Option<DayResult>| match result {
+        Some(result) => format!("{label} day {} ({:+})", result.day, result.change),
+        None => String::new(),
+    };
+    // the latest message, such as where a report was exported
+    let message = app.history.first().map_or(String::new(), |history_item| {
+        history_item.description.clone()
+    });
+
+    let objective = match report.objective_status {
+        _ if !report.objective.has_target() => Line::from(Span::raw("Congratulations!")),
+        ObjectiveStatus::Reached { day } => Line::from(Span::styled(
+            format!("You won! You managed to {} on day {day}", report.objective),
+            Style::default().fg(Color::Green),
+        )),
+        _ => Line::from(Span::styled(
+            format!("You lost, you did not {}", report.objective),
+            ERROR_STYLE,
+        )),
+    };
+
     let text = vec![
-        Line::from(Span::raw("Congratulations!")),
+        objective,
         Line::from(Span::raw(format!(
-            "You earned {} points!",
-            app.game_state.net_worth(),
+            "You earned {} points in {} days! (seed {})",
+            report.net_worth, report.days_played, report.seed
         ))),
-        Line::from(Span::raw("Press q to exit")),
+        Line::from(Span::raw(personal_best)),
+        Line::from(Span::raw(format!(
+            "{}    {}",
+            day("Best", report.best_day),
+            day("Worst", report.worst_day)
+        ))),
+        Line::from(Span::raw(if app.engine.can_undo() {
+            "Press j or m to export the report as JSON or Markdown, u to undo the last day, or q to exit"
+        } else {
+            "Press j or m to export the report as JSON or Markdown, or q to exit"
+        })),
+        Line::from(Span::raw(message)),
     ];
 
     let stats_block = Paragraph::new(text)
@@ -125,8 +661,105 @@ fn draw_game_ended_stats(f: &mut Frame, app: &App, area: Rect) {
     f.render_widget(stats_block, area);
 }
 
+fn draw_report_worth(f: &mut Frame, report: &Report, area: Rect) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(DEFAULT_STYLE)
+        .title(" Net Worth ")
+        .title_alignment(Alignment::Center)
+        .border_type(BorderType::Plain);
+
+    let mut lines = vec![ListItem::new(format!("Money: {}", report.currency))];
+    lines.extend(
+        report
+            .items
+            .iter()
+            .filter(|item| item.amount.is_positive())
+            .map(|item| {
+                if item.sellable {
+                    ListItem::new(format!("{} x{}: {}", item.name, item.amount, item.value))
+                } else {
+                    ListItem::new(format!("{} x{}: cannot be sold", item.name, item.amount))
+                }
+            }),
+    );
+
+    f.render_widget(List::new(lines).block(block), area);
+}
+
+fn draw_report_trades(f: &mut Frame, report: &Report, area: Rect) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(DEFAULT_STYLE)
+        .title(" Trades ")
+        .title_alignment(Alignment::Center)
+        .border_type(BorderType::Plain);
+
+    let statistics = &report.statistics;
+    let mut lines = vec![ListItem::new(format!(
+        "Spent {}, earned {}, {} night market offer(s)",
+        statistics.spent, statistics.earned, statistics.offers_taken
+    ))];
+    lines.extend(report.trades().into_iter().map(
+        |(name, [bought, sold, constructed, deconstructed])| {
+            let parts = [
+                ("bought", bought),
+                ("sold", sold),
+                ("built", constructed),
+                ("torn down", deconstructed),
+            ]
+            .iter()
+            .filter(|(_, count)| count.is_positive())
+            .map(|(label, count)| format!("{label} {count}"))
+            .collect::<Vec<String>>();
+
+            ListItem::new(format!("{name}: {}", parts.join(", ")))
+        },
+    ));
+
+    f.render_widget(List::new(lines).block(block), area);
+}
+
+// the table of the scenario that was played,
+// with the score of this game highlighted
+fn draw_high_scores(f: &mut Frame, app: &App, area: Rect) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(DEFAULT_STYLE)
+        .title(" High Scores ")
+        .title_alignment(Alignment::Center)
+        .border_type(BorderType::Plain);
+
+    let Some(table) = &app.high_score_table else {
+        f.render_widget(Paragraph::new("No high scores").block(block), area);
+        return;
+    };
+
+    let scores = table
+        .scores
+        .iter()
+        .enumerate()
+        .map(|(place, score)| {
+            let line = ListItem::new(format!(
+                "{}. {} {} ({})",
+                place + 1,
+                score.name,
+                score.net_worth,
+                score.date
+            ));
+            if app.recorded_score.as_ref() == Some(score) {
+                line.style(HIGHLIGHT_STYLE)
+            } else {
+                line
+            }
+        })
+        .collect::<Vec<ListItem>>();
+
+    f.render_widget(List::new(scores).block(block), area);
+}
+
 fn draw_tabs(f: &mut Frame, app: &App, area: Rect) {
-    let titles = vec!["Main Game", "Night Market"];
+    let titles = vec!["Main Game", "Night Market", "Research", "Progress"];
 
     let block = Block::default()
         .style(DEFAULT_STYLE)
@@ -137,24 +770,37 @@ fn draw_tabs(f: &mut Frame, app: &App, area: Rect) {
     let tabs = Tabs::new(titles)
         .block(block)
         .highlight_style(HIGHLIGHT_STYLE)
-        .select(0);
+        .select(match app.tab {
+            Tab::MainGame => 0,
+            Tab::NightMarket => 1,
+            Tab::Research => 2,
+            Tab::Progress => 3,
+        });
 
     f.render_widget(tabs, area);
 }
 
-fn draw_cash(f: &mut Frame, app: &App, area: Rect) {
+// cash next to what everything
+// would sell for right now
+fn draw_cash(f: &mut Frame, cash: &str, area: Rect) {
     let block = Block::default()
         .style(DEFAULT_STYLE)
         .borders(Borders::ALL)
         .title_alignment(Alignment::Center)
         .border_type(BorderType::Plain);
 
-    let cash_block = Paragraph::new(format!("$ {}", app.game_state.currency)).block(block);
+    let cash_block = Paragraph::new(cash).block(block);
 
     f.render_widget(cash_block, area);
 }
 
-fn draw_actions(f: &mut Frame, app: &App, area: Rect) {
+fn draw_actions(f: &mut Frame, app: &App, valuation: &Valuation, area: Rect) {
+    let title = match valuation.item(&app.selected_item) {
+        Some(item) if item.sellable => format!(" Actions - worth {} ", item.value),
+        Some(_) => " Actions - cannot be sold ".to_string(),
+        None => " Actions ".to_string(),
+    };
+
     let block = Block::default()
         .border_style(if app.selected_table == Table::Actions {
             HIGHLIGHT_STYLE
@@ -162,7 +808,7 @@ fn draw_actions(f: &mut Frame, app: &App, area: Rect) {
             DEFAULT_STYLE
         })
         .borders(Borders::ALL)
-        .title(" Actions ")
+        .title(title)
         .title_alignment(Alignment::Center)
         .border_type(BorderType::Plain);
 
@@ -174,7 +820,7 @@ fn draw_actions(f: &mut Frame, app: &App, area: Rect) {
 
     f.render_widget(block, area);
     let selected_item_name = app.selected_item.clone();
-    let Some(selected_item) = app.game_state.items.get(&selected_item_name) else {
+    let Some(selected_item) = app.engine.state.items.get(&selected_item_name) else {
         return;
     };
 
@@ -192,7 +838,10 @@ fn draw_actions(f: &mut Frame, app: &App, area: Rect) {
         &mut selected_item
             .actions_passive
             .iter()
-            .map(|p| Constraint::Min(p.description().lines().count() as u16 + 2))
+            .map(|p| {
+                let description = p.current_description(&selected_item_name, &app.engine.state);
+                Constraint::Min(description.lines().count() as u16 + 2)
+            })
             .collect::<Vec<Constraint>>(),
     );
 
@@ -205,7 +854,7 @@ fn draw_actions(f: &mut Frame, app: &App, area: Rect) {
     // use app.selected_item to change
     // the block to one with highlight
     for (i, active) in selected_item.actions_active.iter().enumerate() {
-        let desc = active.description();
+        let desc = active.market_description(&selected_item_name, &app.engine.state);
         let block = action_block.to_owned().border_style(
             if app.selected_table == Table::Actions && app.selection_index == i {
                 HIGHLIGHT_STYLE
@@ -222,7 +871,7 @@ fn draw_actions(f: &mut Frame, app: &App, area: Rect) {
 
     let max_len = action_blocks.len() - 1;
     for (i, passive) in selected_item.actions_passive.iter().enumerate() {
-        let desc = passive.description();
+        let desc = passive.current_description(&selected_item_name, &app.engine.state);
         let action = Paragraph::new(desc.as_str())
             .block(action_block.clone().title(passive.name()))
             .wrap(Wrap { trim: true })
@@ -231,131 +880,304 @@ fn draw_actions(f: &mut Frame, app: &App, area: Rect) {
     }
 }
 
-fn draw_resources(f: &mut Frame, app: &mut App, area: Rect) {
+fn draw_item_table(f: &mut Frame, app: &App, index: usize, area: Rect) {
+    let table = &app.tables[index];
+    let state = &app.engine.state;
+
+    // categories with a capacity show how full they are
+    let title = match storage::category_capacity(state, &table.category) {
+        Some(capacity) => {
+            let used = state
+                .items
+                .values()
+                .filter(|item| item.category == table.category)
+                .map(|item| item.amount)
+                .sum::<Amount>();
+            format!(" {} {used}/{capacity} ", table.title)
+        }
+        None => format!(" {} ", table.title),
+    };
     let block = Block::default()
         .borders(Borders::ALL)
-        .border_style(if app.selected_table == Table::Resources {
+        .border_style(if app.selected_table == Table::Category(index) {
             HIGHLIGHT_STYLE
         } else {
             DEFAULT_STYLE
         })
-        .title(" Resources ")
+        .title(title)
         .title_alignment(Alignment::Center)
         .border_type(BorderType::Plain);
 
-    let resources: Vec<ListItem> = app
-        .resource_table
+    let items: Vec<ListItem> = table
         .items
         .iter()
-        .map(|res_name| {
-            let res_amount = app.game_state.items.get(res_name).unwrap().amount;
-            let char_count = res_name.chars().count();
-            let lines = vec![Line::from(format!(
-                "{res_name}{:>1$.2}",
-                res_amount,
+        .map(|item_name| {
+            let item_amount = state.items.get(item_name).unwrap().amount;
+            let item_amount = match storage::capacity(state, item_name) {
+                Some(capacity) => format!("{item_amount}/{capacity}"),
+                None => item_amount.to_string(),
+            };
+            let char_count = item_name.chars().count();
+            let mut lines = vec![Line::from(format!(
+                "{item_name}{:>1$}",
+                item_amount,
                 (area.width as usize)
                     .checked_sub(char_count)
                     .unwrap_or(0)
                     .checked_sub(5)
                     .unwrap_or(0)
             ))];
+
+            // queued builds of the item below it
+            for (slot, build) in state.build_queue.iter().enumerate() {
+                if build.item != *item_name {
+                    continue;
+                }
+
+                let progress = if construction::is_worked_on(state, slot) {
+                    format!("{} days left", build.days_left)
+                } else {
+                    "waiting for builders".to_string()
+                };
+                lines.push(Line::from(format!(
+                    "  building {}, {progress}",
+                    build.amount
+                )));
+            }
             ListItem::new(lines)
         })
         .collect();
 
-    let resources = List::new(resources)
+    let items = List::new(items)
         .block(block)
         .highlight_style(HIGHLIGHT_STYLE)
         .highlight_symbol("> ");
 
-    let selected_item_name = app.selected_item.clone();
-    if let Some(item) = app.game_state.items.get(&selected_item_name) {
-        if item.category == ItemCategory::Resource {
-            let index = &app
-                .resource_table
-                .items
-                .iter()
-                .position(|s| *s == item.name);
-
-            if let Some(index) = index {
-                f.render_stateful_widget(
-                    resources,
-                    area,
-                    &mut ListState::default().with_selected(Some(*index)),
-                );
-                return;
-            };
-        }
+    let selected = table
+        .items
+        .iter()
+        .position(|item_name| *item_name == app.selected_item);
+
+    if let Some(index) = selected {
+        f.render_stateful_widget(
+            items,
+            area,
+            &mut ListState::default().with_selected(Some(index)),
+        );
+        return;
     };
 
-    f.render_widget(resources, area);
+    f.render_widget(items, area);
 }
 
-fn draw_buildings(f: &mut Frame, app: &mut App, area: Rect) {
+fn draw_night_market(f: &mut Frame, app: &App, area: Rect) {
+    let Some(night_market) = &app.engine.state.night_market else {
+        let closed = Paragraph::new("The night market is closed in this scenario")
+            .alignment(Alignment::Center)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(" Night Market ")
+                    .title_alignment(Alignment::Center)
+                    .border_type(BorderType::Plain),
+            );
+        f.render_widget(closed, area);
+        return;
+    };
+
     let block = Block::default()
         .borders(Borders::ALL)
-        .border_style(if app.selected_table == Table::Buildings {
-            HIGHLIGHT_STYLE
-        } else {
-            DEFAULT_STYLE
-        })
-        .title(" Buildings ")
+        .border_style(HIGHLIGHT_STYLE)
+        .title(format!(
+            " Night Market - new offers in {} days ",
+            night_market.days_until_refresh(app.engine.state.day)
+        ))
         .title_alignment(Alignment::Center)
         .border_type(BorderType::Plain);
 
-    let buildings: Vec<ListItem> = app
-        .building_table
-        .items
+    let offers: Vec<ListItem> = night_market
+        .stocked
         .iter()
-        .map(|build_name| {
-            let build_amount = app.game_state.items.get(build_name).unwrap().amount;
-            let char_count = build_name.chars().count();
-            let lines = vec![Line::from(format!(
-                "{build_name}{:>1$.2}",
-                build_amount,
+        .map(|stocked| {
+            let offer = &night_market.offers[stocked.offer];
+            let remaining = format!("{} left", stocked.remaining);
+            let char_count = offer.name.chars().count();
+            ListItem::new(Line::from(format!(
+                "{}{:>2$}",
+                offer.name,
+                remaining,
                 (area.width as usize)
                     .checked_sub(char_count)
                     .unwrap_or(0)
                     .checked_sub(5)
                     .unwrap_or(0)
-            ))];
-            ListItem::new(lines)
+            )))
         })
         .collect();
 
-    let buildings = List::new(buildings)
+    let offers = List::new(offers)
         .block(block)
         .highlight_style(HIGHLIGHT_STYLE)
         .highlight_symbol("> ");
 
-    let selected_item_name = app.selected_item.clone();
-    if let Some(item) = app.game_state.items.get(&selected_item_name) {
-        if item.category == ItemCategory::Building {
-            let index = &app
-                .building_table
-                .items
-                .iter()
-                .position(|s| *s == item.name);
-
-            if let Some(index) = index {
-                f.render_stateful_widget(
-                    buildings,
-                    area,
-                    &mut ListState::default().with_selected(Some(*index)),
-                );
-                return;
+    f.render_stateful_widget(
+        offers,
+        area,
+        &mut ListState::default().with_selected(Some(app.offer_index)),
+    );
+}
+
+fn draw_offer(f: &mut Frame, app: &App, area: Rect) {
+    let offer = app
+        .engine
+        .state
+        .night_market
+        .as_ref()
+        .and_then(|night_market| night_market.stocked_offer(app.offer_index));
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(DEFAULT_STYLE)
+        .title(match offer {
+            Some((offer, _)) => format!(" {} ", offer.name),
+            None => " Offer ".to_string(),
+        })
+        .title_alignment(Alignment::Center)
+        .border_type(BorderType::Plain);
+
+    let text = match offer {
+        Some((offer, remaining)) => format!("{}\nIn stock: {remaining}", offer.description()),
+        None => String::new(),
+    };
+
+    let offer = Paragraph::new(text)
+        .block(block)
+        .wrap(Wrap { trim: true })
+        .alignment(Alignment::Left);
+    f.render_widget(offer, area);
+}
+
+// every tech indented below its prerequisites
+fn draw_techs(f: &mut Frame, app: &App, area: Rect) {
+    let state = &app.engine.state;
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(HIGHLIGHT_STYLE)
+        .title(match &state.research.current {
+            Some(project) => format!(" Research - {} ", project.tech),
+            None => " Research ".to_string(),
+        })
+        .title_alignment(Alignment::Center)
+        .border_type(BorderType::Plain);
+
+    if state.techs.is_empty() {
+        let empty = Paragraph::new("There is nothing to research in this scenario")
+            .alignment(Alignment::Center)
+            .block(block);
+        f.render_widget(empty, area);
+        return;
+    }
+
+    let techs: Vec<ListItem> = state
+        .techs
+        .iter()
+        .map(|tech| {
+            let name = format!("{}{}", "  ".repeat(research::depth(state, tech)), tech.name);
+            let status = match research::status(state, tech) {
+                TechStatus::Researched => "researched".to_string(),
+                TechStatus::InProgress { days_left } => format!("{days_left} days left"),
+                TechStatus::Available => "available".to_string(),
+                TechStatus::Locked => format!(
+                    "needs {}",
+                    tech.requires
+                        .iter()
+                        .filter(|required| !state.research.is_researched(required))
+                        .cloned()
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                ),
             };
+            let char_count = name.chars().count();
+            ListItem::new(Line::from(format!(
+                "{}{:>2$}",
+                name,
+                status,
+                (area.width as usize)
+                    .checked_sub(char_count)
+                    .unwrap_or(0)
+                    .checked_sub(5)
+                    .unwrap_or(0)
+            )))
+        })
+        .collect();
+
+    let techs = List::new(techs)
+        .block(block)
+        .highlight_style(HIGHLIGHT_STYLE)
+        .highlight_symbol("> ");
+
+    f.render_stateful_widget(
+        techs,
+        area,
+        &mut ListState::default().with_selected(Some(app.tech_index)),
+    );
+}
+
+fn draw_tech(f: &mut Frame, app: &App, area: Rect) {
+    let tech = app.research_selected_tech();
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(DEFAULT_STYLE)
+        .title(match tech {
+            Some(tech) => format!(" {} ", tech.name),
+            None => " Tech ".to_string(),
+        })
+        .title_alignment(Alignment::Center)
+        .border_type(BorderType::Plain);
+
+    let mut lines = Vec::new();
+    if let Some(tech) = tech {
+        if !tech.description.is_empty() {
+            lines.push(tech.description.clone());
+            lines.push(String::new());
         }
-    };
 
-    f.render_widget(buildings, area);
+        let mut costs = Vec::new();
+        if tech.currency_cost != Amount::ZERO {
+            costs.push(format!("$ {}", tech.currency_cost));
+        }
+        let mut item_costs = tech
+            .item_cost
+            .iter()
+            .map(|(name, amount)| format!("{amount} {name}"))
+            .collect::<Vec<String>>();
+        item_costs.sort();
+        costs.extend(item_costs);
+        if !costs.is_empty() {
+            lines.push(format!("Costs: {}", costs.join(", ")));
+        }
+        lines.push(format!("Takes: {} days", tech.days));
+        if !tech.requires.is_empty() {
+            lines.push(format!("Requires: {}", tech.requires.join(", ")));
+        }
+        for unlock in &tech.unlocks {
+            lines.push(unlock.to_string());
+        }
+    }
+
+    let tech = Paragraph::new(lines.join("\n"))
+        .block(block)
+        .wrap(Wrap { trim: true })
+        .alignment(Alignment::Left);
+    f.render_widget(tech, area);
 }
 
 fn draw_history(f: &mut Frame, app: &App, area: Rect) {
     let block = Block::default()
         .borders(Borders::ALL)
         .border_style(DEFAULT_STYLE)
-        .title(" History ")
+        .title(format!(" History (save slot {}) ", app.save_slot))
         .title_alignment(Alignment::Center)
         .border_type(BorderType::Plain);
 
@@ -369,7 +1191,14 @@ fn draw_history(f: &mut Frame, app: &App, area: Rect) {
     let history_items = app
         .history
         .iter()
-        .map(|h| ListItem::new(format!("{} x{}", h.description, h.amount)))
+        .map(|h| {
+            let item = ListItem::new(format!("{} x{}", h.description, h.amount));
+            if h.failed {
+                item.style(ERROR_STYLE)
+            } else {
+                item
+            }
+        })
         .collect::<Vec<ListItem>>();
 
     let history = List::new(history_items).block(block);